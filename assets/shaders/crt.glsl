@@ -0,0 +1,41 @@
+uniform mat3 u_view_matrix;
+uniform mat3 u_projection_matrix;
+
+varying vec2 v_uv;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+attribute vec2 a_uv;
+
+void main() {
+    v_uv = a_uv;
+    vec3 pos = u_projection_matrix * u_view_matrix * vec3(a_pos, 1.0);
+    gl_Position = vec4(pos.xy, 0.0, pos.z);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_texture;
+uniform ivec2 u_texture_size;
+uniform float u_scanlines;
+uniform float u_curvature;
+
+void main() {
+    // Bulge the screen like a CRT tube
+    vec2 centered = v_uv * 2.0 - 1.0;
+    centered *= 1.0 + u_curvature * dot(centered.yx, centered.yx);
+    vec2 uv = centered * 0.5 + 0.5;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec3 color = texture2D(u_texture, uv).rgb;
+
+    // Darken the gaps between the pixel rows
+    float row = sin(uv.y * float(u_texture_size.y) * PI);
+    color *= 1.0 - u_scanlines * (1.0 - row * row);
+
+    gl_FragColor = vec4(color, 1.0);
+}
+#endif
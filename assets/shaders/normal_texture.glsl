@@ -0,0 +1,36 @@
+uniform mat3 u_model_matrix;
+uniform mat3 u_view_matrix;
+uniform mat3 u_projection_matrix;
+// Shifts the texture, used to animate the tiles
+uniform vec2 u_uv_offset;
+
+varying vec2 v_uv;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+attribute vec2 a_uv;
+
+void main() {
+    v_uv = a_uv + u_uv_offset;
+    vec3 pos = u_projection_matrix * u_view_matrix * u_model_matrix * vec3(a_pos, 1.0);
+    gl_Position = vec4(pos.xy, 0.0, pos.z);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_texture;
+// Rotation and flip of the sprite applied to the normals
+uniform mat3 u_normal_matrix;
+uniform float u_normal_influence;
+
+void main() {
+    vec4 normal_color = texture2D(u_texture, v_uv);
+    if (normal_color.a < 0.5) {
+        discard;
+    }
+    vec2 normal = (u_normal_matrix * vec3(normal_color.rg * 2.0 - 1.0, 0.0)).xy;
+    // Encode the normal into the Red and Green channels
+    // The Blue channel is used for the influence coefficient
+    gl_FragColor = vec4(normal * 0.5 + 0.5, u_normal_influence, 1.0);
+}
+#endif
@@ -0,0 +1,36 @@
+uniform mat3 u_view_matrix;
+uniform mat3 u_projection_matrix;
+
+varying vec2 v_quad_pos;
+varying vec4 v_color;
+varying float v_shape;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+attribute vec2 i_pos;
+attribute float i_radius;
+attribute float i_rotation;
+attribute vec4 i_color;
+attribute float i_shape;
+
+void main() {
+    v_quad_pos = a_pos;
+    v_color = i_color;
+    v_shape = i_shape;
+    float c = cos(i_rotation);
+    float s = sin(i_rotation);
+    vec2 pos = i_pos + mat2(c, s, -s, c) * a_pos * i_radius;
+    vec3 screen_pos = u_projection_matrix * u_view_matrix * vec3(pos, 1.0);
+    gl_Position = vec4(screen_pos.xy, 0.0, screen_pos.z);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+void main() {
+    // Circles are cut out of the quad
+    if (v_shape < 0.5 && length(v_quad_pos) > 1.0) {
+        discard;
+    }
+    gl_FragColor = v_color;
+}
+#endif
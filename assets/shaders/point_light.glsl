@@ -0,0 +1,91 @@
+uniform mat3 u_model_matrix;
+uniform mat3 u_view_matrix;
+uniform mat3 u_projection_matrix;
+
+varying vec2 v_quad_pos;
+
+// Height of the lights above the world plane used for the normal shading
+const float LIGHT_HEIGHT = 1.0;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+
+void main() {
+    v_quad_pos = a_pos;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform vec2 u_light_pos;
+uniform vec4 u_light_color;
+uniform float u_light_intensity;
+uniform float u_light_max_distance;
+uniform float u_light_volume;
+uniform int u_light_falloff;
+// Portion of the light passing in this shadow sample
+uniform float u_light_sample_weight;
+uniform sampler2D u_normal_texture;
+uniform sampler2D u_source_texture;
+uniform ivec2 u_framebuffer_size;
+
+// Intensity at the distance, see `LightFalloff`
+float radial_falloff(float distance) {
+    float t = min(distance / u_light_max_distance, 1.0);
+    if (u_light_falloff == 0) {
+        // Linear
+        return 1.0 - t;
+    }
+    if (u_light_falloff == 1) {
+        // Quadratic
+        return (1.0 - t) * (1.0 - t);
+    }
+    if (u_light_falloff == 2) {
+        // Smooth
+        return 1.0 - smoothstep(0.0, 1.0, t);
+    }
+    // Inverse square, windowed to reach zero at the max distance
+    float window = 1.0 - t * t;
+    return window * window / (1.0 + distance * distance);
+}
+
+void main() {
+    // Calculate position relative to the light in world space
+    mat3 transform = u_projection_matrix * u_view_matrix * u_model_matrix;
+    mat3 inv = inverse(transform);
+    vec2 position = (inv * vec3(v_quad_pos, 1.0)).xy - u_light_pos;
+
+    float distance = length(position);
+
+    // Normal falloff
+    // The light is slightly in front of the world so that flat surfaces are lit too
+    vec3 light_dir = normalize(vec3(-position, LIGHT_HEIGHT));
+    vec2 texture_pos = gl_FragCoord.xy / vec2(u_framebuffer_size);
+    vec4 normal_value = texture2D(u_normal_texture, texture_pos);
+    vec2 normal_xy = normal_value.rg * 2.0 - 1.0;
+    vec3 normal = vec3(normal_xy, sqrt(max(1.0 - dot(normal_xy, normal_xy), 0.0)));
+    // Pixels without a normal are lit regardless of the direction
+    float normal_influence = normal_value.b * normal_value.a;
+    float normal_falloff = mix(1.0, clamp(dot(normal, light_dir), 0.0, 1.0), normal_influence);
+
+    // Adjust light intensity based on radial falloff
+    float intensity = u_light_intensity * u_light_sample_weight
+        * radial_falloff(distance) * normal_falloff;
+
+    // Get the base color of the world
+    vec3 base_color = texture2D(u_source_texture, texture_pos).rgb;
+
+    // Adjust light color based on the new intensity
+    vec3 light_color = u_light_color.xyz * intensity;
+    // Shade the world with the normal falloff
+    vec3 shaded_color = base_color * light_color;
+    // Add volumetric lighting
+    shaded_color += light_color * u_light_volume;
+
+    gl_FragColor = vec4(shaded_color, 1.0);
+    // gl_FragColor = vec4(base_color, 1.0);
+    // gl_FragColor = vec4(normal_falloff, 0.0, 0.0, 1.0);
+    // gl_FragColor = vec4(light_dir.xy * 0.5 + 0.5, 0.0, 1.0);
+    // gl_FragColor = vec4(normal * 0.5 + 0.5, 0.0, 1.0);
+}
+#endif
@@ -0,0 +1,51 @@
+varying vec2 v_uv;
+
+// Number of cells along each axis of the LUT
+const float LUT_SIZE = 16.0;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+
+void main() {
+    v_uv = a_pos * 0.5 + 0.5;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_source_texture;
+uniform sampler2D u_bloom_texture;
+uniform float u_bloom_intensity;
+// Color grading LUT as a horizontal strip of blue slices with red along x and green along y
+uniform sampler2D u_lut;
+uniform float u_vignette_strength;
+uniform float u_vignette_radius;
+
+vec3 grade(vec3 color) {
+    color = clamp(color, 0.0, 1.0);
+    float blue = color.b * (LUT_SIZE - 1.0);
+    float slice = floor(blue);
+    float next_slice = min(slice + 1.0, LUT_SIZE - 1.0);
+    vec2 uv = (color.rg * (LUT_SIZE - 1.0) + 0.5) / vec2(LUT_SIZE * LUT_SIZE, LUT_SIZE);
+    vec3 low = texture2D(u_lut, uv + vec2(slice / LUT_SIZE, 0.0)).rgb;
+    vec3 high = texture2D(u_lut, uv + vec2(next_slice / LUT_SIZE, 0.0)).rgb;
+    return mix(low, high, blue - slice);
+}
+
+void main() {
+    vec3 color = texture2D(u_source_texture, v_uv).rgb;
+
+    // Bloom
+    color += texture2D(u_bloom_texture, v_uv).rgb * u_bloom_intensity;
+
+    // Color grading
+    color = grade(color);
+
+    // Vignette
+    float distance = length(v_uv - 0.5) * 2.0;
+    float vignette = smoothstep(u_vignette_radius, u_vignette_radius + 0.6, distance);
+    color *= 1.0 - vignette * u_vignette_strength;
+
+    gl_FragColor = vec4(color, 1.0);
+}
+#endif
@@ -0,0 +1,32 @@
+varying vec2 v_uv;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+
+void main() {
+    v_uv = a_pos * 0.5 + 0.5;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_source_texture;
+uniform ivec2 u_texture_size;
+// Blur direction in pixels, either (1, 0) or (0, 1)
+uniform vec2 u_direction;
+
+void main() {
+    vec2 step = u_direction / vec2(u_texture_size);
+    // 9-tap gaussian
+    vec3 color = texture2D(u_source_texture, v_uv).rgb * 0.227027;
+    color += texture2D(u_source_texture, v_uv + step * 1.0).rgb * 0.1945946;
+    color += texture2D(u_source_texture, v_uv - step * 1.0).rgb * 0.1945946;
+    color += texture2D(u_source_texture, v_uv + step * 2.0).rgb * 0.1216216;
+    color += texture2D(u_source_texture, v_uv - step * 2.0).rgb * 0.1216216;
+    color += texture2D(u_source_texture, v_uv + step * 3.0).rgb * 0.054054;
+    color += texture2D(u_source_texture, v_uv - step * 3.0).rgb * 0.054054;
+    color += texture2D(u_source_texture, v_uv + step * 4.0).rgb * 0.016216;
+    color += texture2D(u_source_texture, v_uv - step * 4.0).rgb * 0.016216;
+    gl_FragColor = vec4(color, 1.0);
+}
+#endif
@@ -0,0 +1,23 @@
+varying vec2 v_uv;
+
+#ifdef VERTEX_SHADER
+attribute vec2 a_pos;
+
+void main() {
+    v_uv = a_pos * 0.5 + 0.5;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT_SHADER
+uniform sampler2D u_source_texture;
+uniform float u_threshold;
+
+void main() {
+    vec3 color = texture2D(u_source_texture, v_uv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    // Keep only the part above the threshold
+    float weight = max(brightness - u_threshold, 0.0) / max(brightness, 0.0001);
+    gl_FragColor = vec4(color * weight, 1.0);
+}
+#endif
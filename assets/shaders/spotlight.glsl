@@ -4,6 +4,9 @@ uniform mat3 u_projection_matrix;
 
 varying vec2 v_quad_pos;
 
+// Height of the lights above the world plane used for the normal shading
+const float LIGHT_HEIGHT = 1.0;
+
 float smooth_step(float min, float max, float value) {
     float t = clamp((value - min) / (max - min), 0.0, 1.0);
     return 3.0 * t * t - 2.0 * t * t * t;
@@ -26,10 +29,34 @@ uniform vec4 u_light_color;
 uniform float u_light_intensity;
 uniform float u_light_max_distance;
 uniform float u_light_volume;
+uniform int u_light_falloff;
+uniform float u_light_angle_softness;
+// Portion of the light passing in this shadow sample
+uniform float u_light_sample_weight;
 uniform sampler2D u_normal_texture;
 uniform sampler2D u_source_texture;
 uniform ivec2 u_framebuffer_size;
 
+// Intensity at the distance, see `LightFalloff`
+float radial_falloff(float distance) {
+    float t = min(distance / u_light_max_distance, 1.0);
+    if (u_light_falloff == 0) {
+        // Linear
+        return 1.0 - t;
+    }
+    if (u_light_falloff == 1) {
+        // Quadratic
+        return (1.0 - t) * (1.0 - t);
+    }
+    if (u_light_falloff == 2) {
+        // Smooth
+        return 1.0 - smooth_step(0.0, 1.0, t);
+    }
+    // Inverse square, windowed to reach zero at the max distance
+    float window = 1.0 - t * t;
+    return window * window / (1.0 + distance * distance);
+}
+
 void main() {
     // Calculate position relative to the light in world space
     mat3 transform = u_projection_matrix * u_view_matrix * u_model_matrix;
@@ -46,26 +73,26 @@ void main() {
         angle += PI * 2.0;
     }
 
-    // Radial falloff
-    float distance_t = min(distance / u_light_max_distance, 1.0);
-    float radial_falloff = (1.0 - distance_t) * (1.0 - distance_t);
-
-    // Angular falloff
-    float angular_falloff = smooth_step(u_light_angle_range, 0.0, abs(angle));
+    // Angular falloff, fully lit inside the inner cone
+    float inner_angle = u_light_angle_range * (1.0 - u_light_angle_softness);
+    float edge = max(u_light_angle_range - inner_angle, 0.0001);
+    float angular_t = clamp((u_light_angle_range - abs(angle)) / edge, 0.0, 1.0);
+    float angular_falloff = angular_t * angular_t * (3.0 - 2.0 * angular_t);
 
     // Normal falloff
-    vec2 light_dir = -position / distance;
+    // The light is slightly in front of the world so that flat surfaces are lit too
+    vec3 light_dir = normalize(vec3(-position, LIGHT_HEIGHT));
     vec2 texture_pos = gl_FragCoord.xy / vec2(u_framebuffer_size);
     vec4 normal_value = texture2D(u_normal_texture, texture_pos);
-    vec2 normal = normal_value.rg * 2.0 - 1.0;
-    float normal_influence = normal_value.b;
-    float normal_falloff = clamp(dot(normal, light_dir), 0.0, 1.0) * normal_influence;
-    if (normal_value.a == 0.0) {
-        normal_falloff = 1.0;
-    }
+    vec2 normal_xy = normal_value.rg * 2.0 - 1.0;
+    vec3 normal = vec3(normal_xy, sqrt(max(1.0 - dot(normal_xy, normal_xy), 0.0)));
+    // Pixels without a normal are lit regardless of the direction
+    float normal_influence = normal_value.b * normal_value.a;
+    float normal_falloff = mix(1.0, clamp(dot(normal, light_dir), 0.0, 1.0), normal_influence);
 
     // Adjust light intensity based on radial and angular falloff
-    float intensity = u_light_intensity * radial_falloff * angular_falloff * normal_falloff;
+    float intensity = u_light_intensity * u_light_sample_weight
+        * radial_falloff(distance) * angular_falloff * normal_falloff;
 
     // Get the base color of the world
     vec3 base_color = texture2D(u_source_texture, texture_pos).rgb;
@@ -80,7 +107,7 @@ void main() {
     gl_FragColor = vec4(shaded_color, 1.0);
     // gl_FragColor = vec4(base_color, 1.0);
     // gl_FragColor = vec4(normal_falloff, 0.0, 0.0, 1.0);
-    // gl_FragColor = vec4(light_dir * 0.5 + 0.5, 0.0, 1.0);
+    // gl_FragColor = vec4(light_dir.xy * 0.5 + 0.5, 0.0, 1.0);
     // gl_FragColor = vec4(normal * 0.5 + 0.5, 0.0, 1.0);
 }
 #endif
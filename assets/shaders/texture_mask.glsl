@@ -1,6 +1,8 @@
 uniform mat3 u_model_matrix;
 uniform mat3 u_view_matrix;
 uniform mat3 u_projection_matrix;
+// Shifts the texture, used to animate the tiles
+uniform vec2 u_uv_offset;
 
 varying vec2 v_uv;
 varying vec2 v_mask_uv;
@@ -11,7 +13,7 @@ attribute vec2 a_uv;
 attribute vec2 a_mask_uv;
 
 void main() {
-    v_uv = a_uv;
+    v_uv = a_uv + u_uv_offset;
     v_mask_uv = a_mask_uv;
     vec3 pos = u_projection_matrix * u_view_matrix * u_model_matrix * vec3(a_pos, 1.0);
     gl_Position = vec4(pos.xy, 0.0, pos.z);
@@ -10,6 +10,13 @@ pub struct Assets {
     #[asset(postprocess = "loop_sound")]
     pub music: geng::Sound,
     pub rules: Rules,
+    pub camera: CameraConfig,
+    pub juice: JuiceConfig,
+    pub screen: ScreenConfig,
+    pub particles: ParticleConfig,
+    pub audio: AudioConfig,
+    pub post: PostConfig,
+    pub grades: ColorGrades,
 }
 
 #[derive(geng::Assets)]
@@ -19,9 +26,17 @@ pub struct Shaders {
     pub grid: ugli::Program,
     pub global_light: ugli::Program,
     pub spotlight: ugli::Program,
+    pub point_light: ugli::Program,
     pub point_light_shadow_map: ugli::Program,
     pub shadow_remove: ugli::Program,
     pub normal_map: ugli::Program,
+    pub normal_texture: ugli::Program,
+    pub normal_texture_mask: ugli::Program,
+    pub post: ugli::Program,
+    pub post_bright: ugli::Program,
+    pub post_blur: ugli::Program,
+    pub crt: ugli::Program,
+    pub particle: ugli::Program,
 }
 
 #[derive(geng::Assets)]
@@ -65,6 +80,10 @@ pub struct Sprites {
     pub cursor: ugli::Texture,
     #[asset(postprocess = "pixel")]
     pub spotlight: ugli::Texture,
+    #[asset(postprocess = "pixel")]
+    pub point_light: ugli::Texture,
+    #[asset(postprocess = "pixel")]
+    pub camera: ugli::Texture,
 }
 
 #[derive(geng::Assets)]
@@ -87,29 +106,49 @@ pub struct PropSprites {
     pub tutorial_drill_use: ugli::Texture,
     #[asset(postprocess = "pixel")]
     pub tutorial_drill_jump: ugli::Texture,
+    #[asset(postprocess = "pixel")]
+    pub tutorial_drill_use_normal: ugli::Texture,
+    #[asset(postprocess = "pixel")]
+    pub tutorial_drill_jump_normal: ugli::Texture,
 }
 
 #[derive(geng::Assets)]
 pub struct PlayerSprites {
-    #[asset(postprocess = "pixel")]
-    pub idle0: ugli::Texture,
-    #[asset(postprocess = "pixel")]
-    pub slide0: ugli::Texture,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"idle.png\"), 0.5)")]
+    pub idle: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"run.png\"), 0.1)")]
+    pub run: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"jump_rise.png\"), 0.1)")]
+    pub jump_rise: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"jump_fall.png\"), 0.1)")]
+    pub jump_fall: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"land.png\"), 0.06)")]
+    pub land: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"wall_slide.png\"), 0.15)")]
+    pub wall_slide: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"death.png\"), 0.08)")]
+    pub death: Animation,
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"finish.png\"), 0.15)")]
+    pub finish: Animation,
     pub drill: DrillSprites,
 }
 
 #[derive(geng::Assets)]
 pub struct DrillSprites {
-    #[asset(postprocess = "pixel")]
-    pub drill_v0: ugli::Texture,
-    #[asset(postprocess = "pixel")]
-    pub drill_d0: ugli::Texture,
+    /// Vertical or horizontal drill spin.
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"drill_v.png\"), 0.05)")]
+    pub drill_v: Animation,
+    /// Diagonal drill spin.
+    #[asset(load_with = "load_sheet(&geng, &base_path.join(\"drill_d.png\"), 0.05)")]
+    pub drill_d: Animation,
 }
 
 #[derive(Deref)]
 pub struct Animation {
     #[deref]
     pub frames: Vec<(ugli::Texture, f32)>,
+    /// Normal maps of the frames, empty if the animation has none.
+    pub normals: Vec<ugli::Texture>,
 }
 
 impl TileSprites {
@@ -122,6 +161,23 @@ impl TileSprites {
     }
 }
 
+impl PlayerSprites {
+    /// Returns the clip for the animation state, the drill clips are chosen by the renderer.
+    pub fn get_animation(&self, state: PlayerAnimationState) -> &Animation {
+        match state {
+            PlayerAnimationState::Idle => &self.idle,
+            PlayerAnimationState::Run => &self.run,
+            PlayerAnimationState::JumpRise => &self.jump_rise,
+            PlayerAnimationState::JumpFall => &self.jump_fall,
+            PlayerAnimationState::Land => &self.land,
+            PlayerAnimationState::WallSlide => &self.wall_slide,
+            PlayerAnimationState::Drill => &self.drill.drill_v,
+            PlayerAnimationState::Death => &self.death,
+            PlayerAnimationState::Finish => &self.finish,
+        }
+    }
+}
+
 impl HazardSprites {
     pub fn get_texture(&self, hazard: &HazardType) -> &ugli::Texture {
         match hazard {
@@ -137,6 +193,13 @@ impl PropSprites {
             PropType::DrillJump => &self.tutorial_drill_jump,
         }
     }
+
+    pub fn get_normal(&self, prop: &PropType) -> &ugli::Texture {
+        match prop {
+            PropType::DrillUse => &self.tutorial_drill_use_normal,
+            PropType::DrillJump => &self.tutorial_drill_jump_normal,
+        }
+    }
 }
 
 fn pixel(texture: &mut ugli::Texture) {
@@ -147,12 +210,47 @@ fn loop_sound(sound: &mut geng::Sound) {
     sound.looped = true;
 }
 
-// impl Animation {
-//     pub fn get_frame(&self, time: Time) -> Option<&ugli::Texture> {
-//         let i = (time.as_f32() * self.frames.len() as f32).floor() as usize;
-//         self.frames.get(i)
-//     }
-// }
+impl Animation {
+    pub fn duration(&self) -> Time {
+        Time::new(self.frames.iter().map(|(_, delay)| delay).sum())
+    }
+
+    /// Returns the frame at the given time, looping the animation.
+    pub fn get_frame(&self, time: Time) -> Option<&ugli::Texture> {
+        self.frame_index(time).map(|i| &self.frames[i].0)
+    }
+
+    /// Returns the frame at the given time, or `None` after the animation has ended.
+    pub fn get_frame_once(&self, time: Time) -> Option<&ugli::Texture> {
+        self.frame_index_once(time).map(|i| &self.frames[i].0)
+    }
+
+    /// Returns the index of the frame at the given time, looping the animation.
+    pub fn frame_index(&self, time: Time) -> Option<usize> {
+        let duration = self.duration();
+        if duration <= Time::ZERO {
+            return (!self.frames.is_empty()).then_some(0);
+        }
+        self.frame_index_once(Time::new(time.as_f32().rem_euclid(duration.as_f32())))
+    }
+
+    /// Returns the index of the frame at the given time,
+    /// or `None` after the animation has ended.
+    pub fn frame_index_once(&self, time: Time) -> Option<usize> {
+        let mut time = time.as_f32();
+        for (i, (_, delay)) in self.frames.iter().enumerate() {
+            if time < *delay {
+                return Some(i);
+            }
+            time -= delay;
+        }
+        None
+    }
+
+    pub fn get_normal(&self, frame: usize) -> Option<&ugli::Texture> {
+        self.normals.get(frame)
+    }
+}
 
 impl geng::LoadAsset for Animation {
     fn load(geng: &Geng, path: &std::path::Path) -> geng::AssetFuture<Self> {
@@ -174,6 +272,7 @@ impl geng::LoadAsset for Animation {
                         (texture, n as f32 / d as f32 / 1000.0)
                     })
                     .collect(),
+                normals: Vec::new(),
             })
         }
         .boxed_local()
@@ -181,6 +280,45 @@ impl geng::LoadAsset for Animation {
     const DEFAULT_EXT: Option<&'static str> = Some("gif");
 }
 
+/// Loads an animation from a horizontal sprite sheet of square frames.
+/// The normal maps are loaded from the `<name>_normal.png` sheet if there is one.
+fn load_sheet(
+    geng: &Geng,
+    path: &std::path::Path,
+    frame_time: f32,
+) -> geng::AssetFuture<Animation> {
+    let data = <Vec<u8> as geng::LoadAsset>::load(geng, path);
+    let name = path.file_stem().unwrap().to_string_lossy();
+    let normal_path = path.with_file_name(format!("{name}_normal.png"));
+    let normal_data = <Vec<u8> as geng::LoadAsset>::load(geng, &normal_path);
+    let geng = geng.clone();
+    async move {
+        let split = |data: &[u8]| -> anyhow::Result<Vec<ugli::Texture>> {
+            let image = image::load_from_memory(data)?.into_rgba8();
+            let size = image.height();
+            Ok((0..image.width() / size)
+                .map(|i| {
+                    let frame =
+                        image::imageops::crop_imm(&image, i * size, 0, size, size).to_image();
+                    let mut texture = ugli::Texture::from_image_image(geng.ugli(), frame);
+                    texture.set_filter(ugli::Filter::Nearest);
+                    texture
+                })
+                .collect())
+        };
+        let frames = split(&data.await?)?
+            .into_iter()
+            .map(|texture| (texture, frame_time))
+            .collect();
+        let normals = match normal_data.await {
+            Ok(data) => split(&data)?,
+            Err(_) => Vec::new(),
+        };
+        Ok(Animation { frames, normals })
+    }
+    .boxed_local()
+}
+
 fn load_font(geng: &Geng, path: &std::path::Path) -> geng::AssetFuture<Rc<geng::Font>> {
     let geng = geng.clone();
     let path = path.to_owned();
@@ -1,31 +1,176 @@
 use super::*;
 
+/// A reversible change of the level.
+/// Applying an action returns the action that undoes it.
 #[derive(Debug, Clone)]
 pub enum Action {
-    Place { block: BlockType, pos: vec2<Coord> },
-    Remove { pos: vec2<Coord> },
-    Replace(Block),
+    Place {
+        block: BlockType,
+        pos: vec2<Coord>,
+    },
+    Remove {
+        pos: vec2<Coord>,
+    },
+    /// Inserts the block at the position given by the id.
+    Insert {
+        id: BlockId,
+        block: Block,
+    },
+    /// Removes the blocks.
+    Delete(Vec<BlockId>),
+    /// Inserts the blocks and selects them, the tiles replace the existing ones.
+    Paste(Vec<Block>),
+    /// Actions done and undone as a single step.
+    Group(Vec<Action>),
+    /// Sets the tiles at the grid positions.
+    SetTiles(Vec<(Tile, vec2<isize>)>),
+    /// Moves the block to the position.
+    Move {
+        id: BlockId,
+        pos: vec2<Coord>,
+    },
+    Resize(vec2<usize>),
+    /// Translates the whole level by the number of cells.
+    Translate(vec2<isize>),
+    SetSpawn(vec2<Coord>),
+    SetFinish(vec2<Coord>),
+    /// Replaces the block edited in the inspector.
+    Edit {
+        id: BlockId,
+        block: Block,
+    },
+    SetGlobalLight(GlobalLightSource),
+    SetGrade(Option<String>),
+    /// Replaces the rule overrides of the level.
+    SetRules(RulesPatch),
+}
+
+impl Action {
+    /// Whether applying the action may change the tiles,
+    /// so that the tile geometry has to be rebuilt.
+    fn changes_tiles(&self) -> bool {
+        match self {
+            Action::Place { block, .. } => matches!(block, BlockType::Tile(_)),
+            // The removed blocks are the hovered ones, which may be tiles
+            Action::Remove { .. } => true,
+            Action::Insert { block, .. } | Action::Edit { block, .. } => {
+                matches!(block, Block::Tile(_))
+            }
+            Action::Delete(ids) => ids.iter().any(|id| matches!(id, BlockId::Tile(_))),
+            Action::Paste(blocks) => blocks.iter().any(|block| matches!(block, Block::Tile(_))),
+            Action::Group(actions) => actions.iter().any(Action::changes_tiles),
+            Action::SetTiles(_) | Action::Resize(_) | Action::Translate(_) => true,
+            Action::Move { .. }
+            | Action::SetSpawn(_)
+            | Action::SetFinish(_)
+            | Action::SetGlobalLight(_)
+            | Action::SetGrade(_)
+            | Action::SetRules(_) => false,
+        }
+    }
 }
 
 impl Editor {
     pub fn action(&mut self, action: Action) {
         self.redo_actions.clear();
-        let undo_action = self.action_impl(action);
-        self.undo_actions.extend(undo_action);
+        let Some(undo_action) = self.action_impl(action) else {
+            return;
+        };
+        match &mut self.stroke {
+            Some(stroke) => stroke.push(undo_action),
+            None => self.undo_actions.push(undo_action),
+        }
     }
 
-    fn action_impl(&mut self, action: Action) -> Vec<Action> {
-        let actions = match action {
-            Action::Place { block, pos } => self.action_place(block, pos),
+    /// Starts grouping the following actions into a single undo step.
+    pub fn start_stroke(&mut self) {
+        self.end_stroke();
+        self.stroke = Some(Vec::new());
+    }
 
-            Action::Remove { pos } => self.action_remove(pos),
-            Action::Replace(block) => self.action_replace(block),
+    /// Pushes the actions done since the start of the stroke as a single undo step.
+    pub fn end_stroke(&mut self) {
+        let Some(mut stroke) = self.stroke.take() else {
+            return;
         };
-        self.geometry = self.level.calculate_geometry(&self.geng, &self.assets);
-        actions
+        stroke.reverse();
+        let undo_action = match stroke.len() {
+            0 => return,
+            1 => stroke.pop().unwrap(),
+            _ => Action::Group(stroke),
+        };
+        self.undo_actions.push(undo_action);
+    }
+
+    fn action_impl(&mut self, action: Action) -> Option<Action> {
+        // The ids may change, so the selection gets invalidated
+        self.selection.clear();
+        let changes_tiles = action.changes_tiles();
+        let undo_action = self.apply_action(action);
+        // Forget the blocks that no longer exist
+        let level = &self.level;
+        self.hovered.retain(|&id| level.get_block(id).is_some());
+        if let Some(id) = self.selected_block {
+            self.inspected = level.get_block(id);
+            if self.inspected.is_none() {
+                self.selected_block = None;
+            }
+        }
+        if changes_tiles {
+            self.geometry = self.level.calculate_geometry(&self.geng, &self.assets);
+            self.light_geometry = self.level.calculate_light_geometry(&self.geng);
+        }
+        undo_action
+    }
+
+    fn apply_action(&mut self, action: Action) -> Option<Action> {
+        match action {
+            Action::Place { block, pos } => self.action_place(block, pos),
+            Action::Remove { pos } => self.action_remove(pos),
+            Action::Insert { id, block } => self.action_insert(id, block),
+            Action::Delete(ids) => self.action_delete(&ids),
+            Action::Paste(blocks) => self.action_paste(blocks),
+            Action::SetTiles(tiles) => self.action_set_tiles(tiles),
+            Action::Group(actions) => {
+                let mut undo: Vec<Action> = actions
+                    .into_iter()
+                    .filter_map(|action| self.apply_action(action))
+                    .collect();
+                undo.reverse();
+                (!undo.is_empty()).then_some(Action::Group(undo))
+            }
+            Action::Move { id, pos } => self.action_move(id, pos),
+            Action::Resize(size) => self.action_resize(size),
+            Action::Translate(delta) => self.action_translate(delta),
+            Action::SetSpawn(pos) => {
+                let old = std::mem::replace(&mut self.level.spawn_point, pos);
+                Some(Action::SetSpawn(old))
+            }
+            Action::SetFinish(pos) => {
+                let old = std::mem::replace(&mut self.level.finish, pos);
+                Some(Action::SetFinish(old))
+            }
+            Action::Edit { id, block } => {
+                let old = self.level.set_block(id, block)?;
+                Some(Action::Edit { id, block: old })
+            }
+            Action::SetGlobalLight(light) => {
+                let old = std::mem::replace(&mut self.level.global_light, light);
+                Some(Action::SetGlobalLight(old))
+            }
+            Action::SetGrade(grade) => {
+                let old = std::mem::replace(&mut self.level.grade, grade);
+                Some(Action::SetGrade(old))
+            }
+            Action::SetRules(rules) => {
+                let old = std::mem::replace(&mut self.level.rules, rules);
+                Some(Action::SetRules(old))
+            }
+        }
     }
 
     pub fn undo(&mut self) {
+        self.end_stroke();
         if let Some(action) = self.undo_actions.pop() {
             let redo_action = self.action_impl(action);
             self.redo_actions.extend(redo_action);
@@ -33,23 +178,25 @@ impl Editor {
     }
 
     pub fn redo(&mut self) {
+        self.end_stroke();
         if let Some(action) = self.redo_actions.pop() {
             let undo_action = self.action_impl(action);
             self.undo_actions.extend(undo_action);
         }
     }
 
-    fn action_place(&mut self, block: BlockType, position: vec2<Coord>) -> Vec<Action> {
+    fn action_place(&mut self, block: BlockType, position: vec2<Coord>) -> Option<Action> {
         let grid_pos = self.level.grid.world_to_grid(position).0;
-        match block {
-            BlockType::Tile(tile) => {
-                self.level.tiles.set_tile_isize(grid_pos, tile);
-            }
+        let level = &mut self.level;
+        let id = match block {
+            BlockType::Tile(tile) => return self.action_set_tiles(vec![(tile, grid_pos)]),
             BlockType::Hazard(hazard) => {
-                self.level.place_hazard(grid_pos, hazard);
+                level.place_hazard(grid_pos, hazard);
+                BlockId::Hazard(level.hazards.len() - 1)
             }
             BlockType::Coin => {
-                self.level.place_coin(grid_pos);
+                level.place_coin(grid_pos);
+                BlockId::Coin(level.coins.len() - 1)
             }
             BlockType::Prop(prop) => {
                 let size = self
@@ -60,43 +207,149 @@ impl Editor {
                     .size()
                     .map(|x| x as f32 / PIXELS_PER_UNIT)
                     .map(Coord::new);
-                self.level.place_prop(grid_pos, size, prop);
+                level.place_prop(grid_pos, size, prop);
+                BlockId::Prop(level.props.len() - 1)
             }
-            BlockType::Spotlight(light) => self
-                .level
-                .spotlights
-                .push(SpotlightSource { position, ..light }),
-        }
-        vec![]
-    }
-
-    fn action_replace(&mut self, block: Block) -> Vec<Action> {
-        match block {
-            Block::Tile((tile, pos)) => {
-                self.level.tiles.set_tile_isize(pos, tile);
-            }
-            Block::Hazard(hazard) => {
-                self.level.hazards.push(hazard);
+            BlockType::Spotlight(light) => {
+                level.spotlights.push(SpotlightSource { position, ..light });
+                BlockId::Spotlight(level.spotlights.len() - 1)
             }
-            Block::Coin(coin) => {
-                self.level.coins.push(coin);
+            BlockType::PointLight(light) => {
+                level
+                    .point_lights
+                    .push(PointLightSource { position, ..light });
+                BlockId::PointLight(level.point_lights.len() - 1)
             }
-            Block::Prop(prop) => {
-                self.level.props.push(prop);
+            BlockType::CameraZone(mode) => {
+                level.place_camera_zone(position, mode);
+                BlockId::CameraZone(level.camera_zones.len() - 1)
             }
-            Block::Spotlight(spotlight) => self.level.spotlights.push(spotlight),
+        };
+        Some(Action::Delete(vec![id]))
+    }
+
+    fn action_insert(&mut self, id: BlockId, block: Block) -> Option<Action> {
+        if let Block::Tile((tile, pos)) = block {
+            return self.action_set_tiles(vec![(tile, pos)]);
         }
-        vec![]
+        let id = self.level.insert_block_at(id, block)?;
+        Some(Action::Delete(vec![id]))
     }
 
-    fn action_remove(&mut self, _pos: vec2<Coord>) -> Vec<Action> {
-        let actions = self
-            .level
-            .remove_blocks(&self.hovered)
+    /// Returns the action inserting back the removed blocks at their positions.
+    fn undo_remove(removed: Vec<(BlockId, Block)>) -> Option<Action> {
+        if removed.is_empty() {
+            return None;
+        }
+        // The blocks are removed from the last one, so they are inserted back from the first one
+        let insert = removed
             .into_iter()
-            .map(Action::Replace)
+            .rev()
+            .map(|(id, block)| Action::Insert { id, block })
             .collect();
+        Some(Action::Group(insert))
+    }
+
+    fn action_delete(&mut self, ids: &[BlockId]) -> Option<Action> {
+        let removed = self.level.remove_blocks(ids);
+        Self::undo_remove(removed)
+    }
+
+    fn action_paste(&mut self, blocks: Vec<Block>) -> Option<Action> {
+        let mut inserted = Vec::new();
+        let mut replaced = Vec::new();
+        for block in blocks {
+            if let Block::Tile((_, pos)) = block {
+                let Some(tile) = self.level.tiles.get_tile_isize(pos) else {
+                    // Outside of the level
+                    continue;
+                };
+                replaced.push((tile, pos));
+                self.selection.push(self.level.insert_block(block));
+            } else {
+                inserted.push(self.level.insert_block(block));
+            }
+        }
+        self.selection.extend(inserted.iter().copied());
+
+        Some(Action::Group(vec![
+            Action::Delete(inserted),
+            Action::SetTiles(replaced),
+        ]))
+    }
+
+    fn action_set_tiles(&mut self, tiles: Vec<(Tile, vec2<isize>)>) -> Option<Action> {
+        let mut replaced = Vec::new();
+        for (tile, pos) in tiles {
+            match self.level.tiles.get_tile_isize(pos) {
+                Some(old) if old != tile => {
+                    self.level.tiles.set_tile_isize(pos, tile);
+                    replaced.push((old, pos));
+                }
+                _ => {}
+            }
+        }
+        (!replaced.is_empty()).then_some(Action::SetTiles(replaced))
+    }
+
+    fn action_remove(&mut self, _pos: vec2<Coord>) -> Option<Action> {
+        let removed = self.level.remove_blocks(&self.hovered);
         self.hovered.clear();
-        actions
+        Self::undo_remove(removed)
+    }
+
+    fn action_move(&mut self, id: BlockId, pos: vec2<Coord>) -> Option<Action> {
+        let old = match self.level.get_block(id)? {
+            // Tiles are moved with the selection
+            Block::Tile(_) => return None,
+            block => block.position(),
+        };
+        self.move_block(id, pos);
+        Some(Action::Move { id, pos: old })
+    }
+
+    fn action_resize(&mut self, size: vec2<usize>) -> Option<Action> {
+        let old = self.level.size;
+        if size == old || size.x == 0 || size.y == 0 {
+            return None;
+        }
+        // The tiles cut off by shrinking are restored on undo
+        let lost = self.non_air_tiles(|pos| pos.x >= size.x || pos.y >= size.y);
+        self.level.change_size(size);
+        Some(Action::Group(vec![
+            Action::Resize(old),
+            Action::SetTiles(lost),
+        ]))
+    }
+
+    fn action_translate(&mut self, delta: vec2<isize>) -> Option<Action> {
+        if delta == vec2::ZERO {
+            return None;
+        }
+        // The tiles moved outside of the level are restored on undo
+        let size = self.level.size.map(|x| x as isize);
+        let lost = self.non_air_tiles(|pos| {
+            let pos = pos.map(|x| x as isize) + delta;
+            pos.x < 0 || pos.y < 0 || pos.x >= size.x || pos.y >= size.y
+        });
+        self.level.translate(delta);
+        Some(Action::Group(vec![
+            Action::Translate(-delta),
+            Action::SetTiles(lost),
+        ]))
+    }
+
+    fn non_air_tiles(&self, filter: impl Fn(vec2<usize>) -> bool) -> Vec<(Tile, vec2<isize>)> {
+        let width = self.level.tiles.size.x;
+        self.level
+            .tiles
+            .tiles()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| !matches!(tile, Tile::Air))
+            .map(|(i, &tile)| (tile, index_to_pos(i, width)))
+            .filter(|&(_, pos)| filter(pos))
+            .map(|(tile, pos)| (tile, pos.map(|x| x as isize)))
+            .collect()
     }
 }
@@ -1,9 +1,15 @@
 use super::*;
 
 mod action;
+mod play;
+mod selection;
+mod tools;
 mod ui_impl;
 
 use action::*;
+use play::*;
+use selection::*;
+use tools::*;
 
 const CAMERA_MOVE_SPEED: f32 = 20.0;
 
@@ -16,27 +22,53 @@ struct Render {
 pub struct Editor {
     geng: Geng,
     assets: Rc<Assets>,
-    pixel_texture: ugli::Texture,
+    screen: PixelScreen,
     render: Render,
     camera: Camera2d,
     framebuffer_size: vec2<usize>,
     level_name: String,
     level: Level,
     geometry: (
-        HashMap<Tile, ugli::VertexBuffer<Vertex>>,
-        HashMap<Tile, ugli::VertexBuffer<MaskedVertex>>,
+        HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
     ),
     light_geometry: Vec<StaticPolygon>,
+    /// Time since the editor was opened, used to preview the light animations.
+    time: f32,
     draw_grid: bool,
     cursor_pos: vec2<f64>,
     cursor_world_pos: vec2<Coord>,
     dragging: Option<Dragging>,
     selected_block: Option<BlockId>,
+    /// Copy of the selected block edited in the inspector.
+    inspected: Option<Block>,
+    /// Whether the inspected block has been changed in the ui since the last update.
+    inspected_changed: bool,
+    /// Blocks selected with the rectangle selection.
+    selection: Vec<BlockId>,
+    clipboard: Option<Clipboard>,
+    tile_tool: TileTool,
     tabs: Vec<EditorTab>,
     active_tab: usize,
     undo_actions: Vec<Action>,
     redo_actions: Vec<Action>,
+    /// Undo actions of the drag in progress, pushed as a single step once it ends.
+    stroke: Option<Vec<Action>>,
+    /// Actions requested by the ui, applied on the next update.
+    ui_actions: Vec<Action>,
+    /// Whether a slider in the ui is being dragged.
+    ui_dragging: bool,
+    /// Whether the stroke in progress has been started by dragging a slider.
+    ui_stroke: bool,
     hovered: Vec<BlockId>,
+    /// The level being play-tested.
+    play: Option<PlayTest>,
+    /// The trace of the last play-test.
+    trace: Option<PlayTrace>,
+    show_trace: bool,
+    /// The level file being uploaded by the user.
+    #[cfg(target_arch = "wasm32")]
+    upload: Option<futures::channel::oneshot::Receiver<(String, String)>>,
 }
 
 #[derive(Debug)]
@@ -54,6 +86,14 @@ enum DragAction {
         id: BlockId,
         initial_pos: vec2<Coord>,
     },
+    /// Rectangle selection.
+    Select,
+    MoveSelection,
+    /// Rectangle or line of tiles, placed on release.
+    TileShape {
+        tile: Tile,
+        from: vec2<isize>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -70,23 +110,18 @@ enum EditorMode {
         blocks: Vec<BlockType>,
         selected: usize,
     },
-    Lights,
+    Lights {
+        blocks: Vec<BlockType>,
+        selected: usize,
+    },
 }
 
 impl Editor {
-    pub fn new(geng: &Geng, assets: &Rc<Assets>, level_name: Option<String>) -> Self {
-        let level_name = level_name.unwrap_or_else(|| "new_level.json".to_string());
-        let level =
-            util::report_err(Level::load(&level_name), "Failed to load level").unwrap_or_default();
+    pub fn new(geng: &Geng, assets: &Rc<Assets>, level_name: String, level: Level) -> Self {
         Self {
             geng: geng.clone(),
             assets: assets.clone(),
-            pixel_texture: {
-                let mut texture =
-                    ugli::Texture::new_with(geng.ugli(), SCREEN_RESOLUTION, |_| Rgba::BLACK);
-                texture.set_filter(ugli::Filter::Nearest);
-                texture
-            },
+            screen: PixelScreen::new(geng, &assets.screen),
             render: Render {
                 world: WorldRender::new(geng, assets),
                 lights: LightsRender::new(geng, assets),
@@ -95,16 +130,22 @@ impl Editor {
             camera: Camera2d {
                 center: vec2(0.0, 0.25),
                 rotation: 0.0,
-                fov: 22.5,
+                fov: DEFAULT_FOV,
             },
             framebuffer_size: vec2(1, 1),
             geometry: level.calculate_geometry(geng, assets),
             light_geometry: level.calculate_light_geometry(geng),
+            time: 0.0,
             draw_grid: true,
             cursor_pos: vec2::ZERO,
             cursor_world_pos: vec2::ZERO,
             dragging: None,
             selected_block: None,
+            inspected: None,
+            inspected_changed: false,
+            selection: Vec::new(),
+            clipboard: None,
+            tile_tool: TileTool::Brush,
             tabs: vec![
                 EditorTab {
                     name: "Level".into(),
@@ -131,24 +172,55 @@ impl Editor {
                     "Props",
                     PropType::all().into_iter().map(BlockType::Prop).collect(),
                 ),
-                EditorTab {
-                    name: "Lights".into(),
-                    hoverable: vec![BlockType::Spotlight(default())],
-                    mode: EditorMode::Lights,
-                },
+                EditorTab::lights(vec![
+                    BlockType::Spotlight(default()),
+                    BlockType::PointLight(default()),
+                ]),
+                EditorTab::block(
+                    "Camera",
+                    CameraZoneMode::all()
+                        .into_iter()
+                        .map(BlockType::CameraZone)
+                        .collect(),
+                ),
             ],
             active_tab: 0,
             undo_actions: default(),
             redo_actions: default(),
+            stroke: None,
+            ui_actions: Vec::new(),
+            ui_dragging: false,
+            ui_stroke: false,
             hovered: Vec::new(),
+            play: None,
+            trace: None,
+            show_trace: true,
+            #[cfg(target_arch = "wasm32")]
+            upload: None,
             level,
             level_name,
         }
     }
 
+    fn set_level(&mut self, level_name: String, level: Level) {
+        self.geometry = level.calculate_geometry(&self.geng, &self.assets);
+        self.light_geometry = level.calculate_light_geometry(&self.geng);
+        self.level = level;
+        self.level_name = level_name;
+        self.deselect_block();
+        self.selection.clear();
+        self.hovered.clear();
+        self.undo_actions.clear();
+        self.redo_actions.clear();
+        self.stroke = None;
+        self.trace = None;
+    }
+
     fn scroll_selected(&mut self, delta: isize) {
         if let Some(tab) = self.tabs.get_mut(self.active_tab) {
-            if let EditorMode::Block { selected, blocks } = &mut tab.mode {
+            if let EditorMode::Block { selected, blocks }
+            | EditorMode::Lights { selected, blocks } = &mut tab.mode
+            {
                 let current = *selected as isize;
                 let target = current + delta;
                 *selected = target.rem_euclid(blocks.len() as isize) as usize;
@@ -161,8 +233,8 @@ impl Editor {
             .get(self.active_tab)
             .and_then(|tab| match &tab.mode {
                 EditorMode::Level => None,
-                EditorMode::Block { blocks, selected } => blocks.get(*selected).copied(),
-                EditorMode::Lights => Some(BlockType::Spotlight(default())),
+                EditorMode::Block { blocks, selected }
+                | EditorMode::Lights { blocks, selected } => blocks.get(*selected).copied(),
             })
     }
 
@@ -182,48 +254,47 @@ impl Editor {
     }
 
     fn move_block(&mut self, id: BlockId, pos: vec2<Coord>) {
-        match id {
-            BlockId::Tile(_) => unimplemented!(),
-            BlockId::Hazard(id) => {
-                if let Some(hazard) = self.level.hazards.get_mut(id) {
-                    hazard.teleport(pos);
-                }
-            }
-            BlockId::Prop(id) => {
-                if let Some(prop) = self.level.props.get_mut(id) {
-                    prop.teleport(pos);
-                }
-            }
-            BlockId::Coin(id) => {
-                if let Some(coin) = self.level.coins.get_mut(id) {
-                    coin.teleport(pos);
-                }
-            }
-            BlockId::Spotlight(id) => {
-                if let Some(light) = self.level.spotlights.get_mut(id) {
-                    light.position = pos;
-                }
-            }
+        if let Some(mut block) = self.level.get_block(id) {
+            block.set_position(pos);
+            self.level.set_block(id, block);
         }
     }
 
     fn select_block(&mut self, id: BlockId) {
         self.selected_block = Some(id);
-        let Some(_block) = self.level.get_block(id) else {
-            return;
-        };
+        self.inspected = self.level.get_block(id);
+        self.inspected_changed = false;
     }
 
+    fn deselect_block(&mut self) {
+        self.selected_block = None;
+        self.inspected = None;
+        self.inspected_changed = false;
+    }
+
+    /// Applies the changes made in the inspector to the level.
     fn update_selected_block(&mut self) {
-        let Some(_id) = self.selected_block else {
+        let Some(id) = self.selected_block else {
             return;
         };
+        if !std::mem::take(&mut self.inspected_changed) {
+            return;
+        }
+        if let Some(block) = self.inspected.clone() {
+            self.action(Action::Edit { id, block });
+        }
+    }
+
+    /// The camera used to draw over the pixel screen directly onto the window.
+    fn window_camera(&self) -> Camera2d {
+        self.screen
+            .window_camera(&self.camera, self.framebuffer_size)
     }
 
     fn update_cursor(&mut self, cursor_pos: vec2<f64>) {
         self.cursor_pos = cursor_pos;
         self.cursor_world_pos = self
-            .camera
+            .window_camera()
             .screen_to_world(
                 self.framebuffer_size.map(|x| x as f32),
                 cursor_pos.map(|x| x as f32),
@@ -241,10 +312,14 @@ impl Editor {
                 match action {
                     DragAction::PlaceTile => self.place_block(),
                     DragAction::RemoveTile => self.remove_block(),
-                    &DragAction::MoveBlock { id, initial_pos } => self.move_block(
+                    &DragAction::MoveBlock { id, initial_pos } => self.action(Action::Move {
                         id,
-                        initial_pos + self.cursor_world_pos - dragging.initial_world_pos,
-                    ),
+                        pos: initial_pos + self.cursor_world_pos - dragging.initial_world_pos,
+                    }),
+                    // Applied on release
+                    DragAction::Select
+                    | DragAction::MoveSelection
+                    | DragAction::TileShape { .. } => {}
                 }
             }
         }
@@ -254,10 +329,30 @@ impl Editor {
         self.release(button);
         self.update_cursor(position);
 
+        let tile_shape = self.tool_tile(button).and_then(|tile| {
+            self.tile_tool.is_shape().then(|| DragAction::TileShape {
+                tile,
+                from: self.level.grid.world_to_grid(self.cursor_world_pos).0,
+            })
+        });
+
         let action = match button {
             geng::MouseButton::Left => {
-                if let Some(BlockType::Tile(_)) = self.selected_block() {
-                    Some(DragAction::PlaceTile)
+                // Shift always starts a new selection,
+                // the level tab selects unless the selection is dragged
+                let shift = self.geng.window().is_key_pressed(geng::Key::LShift);
+                let level_tab = matches!(
+                    self.tabs.get(self.active_tab).map(|tab| &tab.mode),
+                    Some(EditorMode::Level)
+                );
+                if !shift && self.selection_contains(self.cursor_world_pos) {
+                    Some(DragAction::MoveSelection)
+                } else if shift || level_tab {
+                    Some(DragAction::Select)
+                } else if tile_shape.is_some() {
+                    tile_shape
+                } else if let Some(BlockType::Tile(_)) = self.selected_block() {
+                    (self.tile_tool == TileTool::Brush).then_some(DragAction::PlaceTile)
                 } else if let Some(&id) = self.hovered.first() {
                     self.level.get_block(id).map(|block| DragAction::MoveBlock {
                         id,
@@ -268,8 +363,10 @@ impl Editor {
                 }
             }
             geng::MouseButton::Right => {
-                if let Some(BlockType::Tile(_)) = self.selected_block() {
-                    Some(DragAction::RemoveTile)
+                if tile_shape.is_some() {
+                    tile_shape
+                } else if let Some(BlockType::Tile(_)) = self.selected_block() {
+                    (self.tile_tool == TileTool::Brush).then_some(DragAction::RemoveTile)
                 } else {
                     None
                 }
@@ -277,7 +374,9 @@ impl Editor {
             geng::MouseButton::Middle => None,
         };
 
-        self.selected_block = None;
+        self.deselect_block();
+        // Everything done until the release is undone at once
+        self.start_stroke();
         self.dragging = Some(Dragging {
             initial_cursor_pos: position,
             initial_world_pos: self.cursor_world_pos,
@@ -291,19 +390,41 @@ impl Editor {
         if let Some(dragging) = self.dragging.take() {
             if dragging.initial_cursor_pos == self.cursor_pos {
                 // Click
-                match button {
-                    geng::MouseButton::Left => {
-                        if let Some(&id) = self.hovered.first() {
-                            self.select_block(id);
-                        } else {
-                            self.place_block()
+                self.selection.clear();
+                let fill = self
+                    .tool_tile(button)
+                    .filter(|_| self.tile_tool == TileTool::Fill);
+                if let Some(tile) = fill {
+                    self.flood_fill(tile);
+                } else {
+                    match button {
+                        geng::MouseButton::Left => {
+                            if let Some(&id) = self.hovered.first() {
+                                self.select_block(id);
+                            } else {
+                                self.place_block()
+                            }
                         }
+                        geng::MouseButton::Right => self.remove_block(),
+                        geng::MouseButton::Middle => {}
+                    }
+                }
+            } else {
+                match dragging.action {
+                    Some(DragAction::Select) => self.select_area(Aabb2::from_corners(
+                        dragging.initial_world_pos,
+                        self.cursor_world_pos,
+                    )),
+                    Some(DragAction::MoveSelection) => {
+                        let cells = self.selection_drag_cells(dragging.initial_world_pos);
+                        self.move_selection(cells);
                     }
-                    geng::MouseButton::Right => self.remove_block(),
-                    geng::MouseButton::Middle => {}
+                    Some(DragAction::TileShape { tile, from }) => self.place_shape(tile, from),
+                    _ => {}
                 }
             }
         }
+        self.end_stroke();
     }
 
     fn save_level(&self) {
@@ -312,23 +433,67 @@ impl Editor {
             info!("Saved the level");
         }
     }
+
+    /// Asks the user to select a level file to open.
+    #[cfg(target_arch = "wasm32")]
+    fn upload_level(&mut self) {
+        if let Ok(receiver) = util::report_err(web::upload(), "Failed to open the file dialog") {
+            self.upload = Some(receiver);
+        }
+    }
+
+    /// Opens the uploaded level once it has been read.
+    #[cfg(target_arch = "wasm32")]
+    fn poll_upload(&mut self) {
+        let Some(receiver) = &mut self.upload else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(None) => {}
+            Ok(Some((name, data))) => {
+                self.upload = None;
+                if let Ok(level) =
+                    util::report_err(serde_json::from_str(&data), "Failed to parse the level")
+                {
+                    self.set_level(name, level);
+                    info!("Opened the level");
+                }
+            }
+            Err(_) => {
+                // Cancelled
+                self.upload = None;
+            }
+        }
+    }
 }
 
 impl geng::State for Editor {
     fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
         self.framebuffer_size = framebuffer.size();
+        if let Some(play) = &mut self.play {
+            ugli::clear(framebuffer, Some(Rgba::BLACK), None, None);
+            play.draw(&mut self.screen, framebuffer);
+            if self.show_trace {
+                let camera = self
+                    .screen
+                    .window_camera(play.camera(), self.framebuffer_size);
+                play.trace()
+                    .draw(&self.geng, &self.assets, &camera, framebuffer);
+            }
+            return;
+        }
+
         let color = Rgba::try_from("#341a22").unwrap();
         ugli::clear(framebuffer, Some(color), None, None);
 
         // Render the game onto the texture
-        let mut pixel_framebuffer = ugli::Framebuffer::new_color(
-            self.geng.ugli(),
-            ugli::ColorAttachment::Texture(&mut self.pixel_texture),
-        );
+        self.screen.update(framebuffer.size());
+        self.camera.fov = self.screen.resolution().y as f32 / PIXELS_PER_UNIT;
+        let mut pixel_framebuffer = self.screen.framebuffer();
         ugli::clear(&mut pixel_framebuffer, Some(Rgba::BLACK), None, None);
 
         // Draw the world and normals ignoring lighting
-        let (mut world_framebuffer, _normal_framebuffer) =
+        let (mut world_framebuffer, mut normal_framebuffer) =
             self.render.lights.start_render(&mut pixel_framebuffer);
 
         // Render level
@@ -336,33 +501,46 @@ impl geng::State for Editor {
             &self.level,
             &self.geometry.0,
             &self.geometry.1,
+            Time::new(self.time),
             true,
             &self.camera,
             &mut world_framebuffer,
         );
+        self.render.world.draw_level_normals(
+            &self.level,
+            &self.geometry.0,
+            &self.geometry.1,
+            Time::new(self.time),
+            &self.camera,
+            &mut normal_framebuffer,
+        );
 
         self.render.lights.finish_render(
-            &self.level,
+            &self
+                .level
+                .scene_lights(self.time, (self.level.spawn_point, 0.0)),
+            self.level.grade.as_deref(),
             &self.light_geometry,
             &self.camera,
             &mut pixel_framebuffer,
         );
 
         // Render the texture onto the screen
-        let reference_size = vec2(16.0, 9.0);
-        let ratio = framebuffer.size().map(|x| x as f32) / reference_size;
-        let ratio = ratio.x.min(ratio.y);
-        let target_size = reference_size * ratio;
-        let target = Aabb2::point(framebuffer.size().map(|x| x as f32) / 2.0)
-            .extend_symmetric(target_size / 2.0);
-        self.geng.draw_2d(
-            framebuffer,
-            &geng::PixelPerfectCamera,
-            &draw_2d::TexturedQuad::new(target, &self.pixel_texture),
-        );
+        self.screen.draw(framebuffer);
 
         // Draw hovered
         let mut colliders = Vec::new();
+        if let Some(tab) = self.tabs.get(self.active_tab) {
+            if tab
+                .hoverable
+                .iter()
+                .any(|ty| matches!(ty, BlockType::CameraZone(_)))
+            {
+                for zone in &self.level.camera_zones {
+                    colliders.push((Collider::new(zone.area), Rgba::new(0.0, 0.5, 1.0, 0.1)));
+                }
+            }
+        }
         for &block in itertools::chain![&self.hovered, &self.selected_block] {
             match block {
                 BlockId::Tile(_) => {}
@@ -386,26 +564,96 @@ impl geng::State for Editor {
                     color.a = 0.5;
                     colliders.push((collider, color));
                 }
+                BlockId::PointLight(id) => {
+                    let light = &self.level.point_lights[id];
+                    let collider =
+                        Collider::new(Aabb2::point(light.position).extend_uniform(Coord::new(0.5)));
+                    let mut color = light.color;
+                    color.a = 0.5;
+                    colliders.push((collider, color));
+                }
+                BlockId::CameraZone(id) => {
+                    let zone = &self.level.camera_zones[id];
+                    colliders.push((Collider::new(zone.area), Rgba::new(0.0, 0.5, 1.0, 0.3)));
+                }
+            }
+        }
+
+        // Draw the selection, moved along when dragged
+        let drag_offset = match &self.dragging {
+            Some(Dragging {
+                action: Some(DragAction::MoveSelection),
+                initial_world_pos,
+                ..
+            }) => {
+                let cells = self.selection_drag_cells(*initial_world_pos);
+                let grid = &self.level.grid;
+                grid.grid_to_world(cells) - grid.grid_to_world(vec2::ZERO)
+            }
+            _ => vec2::ZERO,
+        };
+        for &id in &self.selection {
+            if let Some(bounds) = self.level.block_bounds(id) {
+                let collider = Collider::new(bounds.translate(drag_offset));
+                colliders.push((collider, Rgba::new(0.3, 0.6, 1.0, 0.4)));
+            }
+        }
+        if let Some(Dragging {
+            action: Some(DragAction::Select),
+            initial_world_pos,
+            ..
+        }) = &self.dragging
+        {
+            let area = Aabb2::from_corners(*initial_world_pos, self.cursor_world_pos);
+            colliders.push((Collider::new(area), Rgba::new(0.3, 0.6, 1.0, 0.2)));
+        }
+        if let Some(Dragging {
+            action: Some(DragAction::TileShape { tile, from }),
+            ..
+        }) = &self.dragging
+        {
+            // Preview the shape before placing it
+            let color = match tile {
+                Tile::Air => Rgba::new(1.0, 0.2, 0.2, 0.4),
+                _ => Rgba::new(1.0, 1.0, 1.0, 0.4),
+            };
+            let grid = &self.level.grid;
+            for pos in self.shape_cells(*from) {
+                let cell = Aabb2::point(grid.grid_to_world(pos)).extend_positive(grid.cell_size);
+                colliders.push((Collider::new(cell), color));
             }
         }
+
+        let window_camera = self.window_camera();
         for (collider, color) in colliders {
             self.render
                 .util
-                .draw_collider(&collider, color, &self.camera, framebuffer);
+                .draw_collider(&collider, color, &window_camera, framebuffer);
+        }
+
+        if self.show_trace {
+            if let Some(trace) = &self.trace {
+                trace.draw(&self.geng, &self.assets, &window_camera, framebuffer);
+            }
         }
 
         if self.draw_grid {
             self.render.util.draw_grid(
                 &self.level.grid,
                 self.level.size,
-                &self.camera,
+                &window_camera,
                 framebuffer,
             );
         }
     }
 
     fn update(&mut self, delta_time: f64) {
+        if self.play.is_some() {
+            return;
+        }
+
         let delta_time = delta_time as f32;
+        self.time += delta_time;
         let window = self.geng.window();
         let mut dir = vec2::ZERO;
         if window.is_key_pressed(geng::Key::A) {
@@ -422,10 +670,45 @@ impl geng::State for Editor {
         }
         self.camera.center += dir * CAMERA_MOVE_SPEED * delta_time;
 
+        #[cfg(target_arch = "wasm32")]
+        self.poll_upload();
+
+        // The changes done while dragging a slider are undone at once
+        if self.ui_dragging && !self.ui_stroke {
+            self.start_stroke();
+            self.ui_stroke = true;
+        }
+        for action in std::mem::take(&mut self.ui_actions) {
+            self.action(action);
+        }
         self.update_selected_block();
+        if !self.ui_dragging && self.ui_stroke {
+            self.end_stroke();
+            self.ui_stroke = false;
+        }
+    }
+
+    fn fixed_update(&mut self, delta_time: f64) {
+        if let Some(play) = &mut self.play {
+            if play.update(self.geng.window(), Time::new(delta_time as f32)) {
+                info!("Reached the finish");
+                self.stop_play();
+            }
+        }
     }
 
     fn handle_event(&mut self, event: geng::Event) {
+        if let Some(play) = &mut self.play {
+            if let geng::Event::KeyDown { key } = event {
+                match key {
+                    geng::Key::P | geng::Key::Escape => self.stop_play(),
+                    geng::Key::O => self.show_trace = !self.show_trace,
+                    _ => play.key_down(key),
+                }
+            }
+            return;
+        }
+
         match event {
             geng::Event::MouseDown { position, button } => {
                 self.click(position, button);
@@ -444,6 +727,21 @@ impl geng::State for Editor {
                 geng::Key::S if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
                     self.save_level();
                 }
+                geng::Key::C if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                    self.copy_selection();
+                }
+                geng::Key::X if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                    self.cut_selection();
+                }
+                geng::Key::V if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
+                    self.paste();
+                }
+                geng::Key::Delete => {
+                    self.delete_selection();
+                }
+                geng::Key::Escape => {
+                    self.selection.clear();
+                }
                 geng::Key::Z if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
                     if self.geng.window().is_key_pressed(geng::Key::LShift) {
                         self.redo();
@@ -451,11 +749,24 @@ impl geng::State for Editor {
                         self.undo();
                     }
                 }
+                geng::Key::T => {
+                    let tools = TileTool::all();
+                    let i = tools.iter().position(|&tool| tool == self.tile_tool);
+                    self.tile_tool = tools[i.map_or(0, |i| (i + 1) % tools.len())];
+                }
+                geng::Key::P => {
+                    // Shift spawns the player at the cursor
+                    let at_cursor = self.geng.window().is_key_pressed(geng::Key::LShift);
+                    self.start_play(at_cursor);
+                }
+                geng::Key::O => {
+                    self.show_trace = !self.show_trace;
+                }
                 geng::Key::R => {
-                    self.level.spawn_point = self.cursor_world_pos;
+                    self.action(Action::SetSpawn(self.cursor_world_pos));
                 }
                 geng::Key::F => {
-                    self.level.finish = self.cursor_world_pos;
+                    self.action(Action::SetFinish(self.cursor_world_pos));
                 }
                 geng::Key::Left => {
                     self.scroll_selected(-1);
@@ -470,6 +781,9 @@ impl geng::State for Editor {
     }
 
     fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        if self.play.is_some() {
+            return Box::new(geng::ui::Void);
+        }
         self.ui(cx)
     }
 }
@@ -485,6 +799,17 @@ impl EditorTab {
             },
         }
     }
+
+    pub fn lights(blocks: Vec<BlockType>) -> Self {
+        Self {
+            name: "Lights".into(),
+            hoverable: blocks.clone(),
+            mode: EditorMode::Lights {
+                selected: 0,
+                blocks,
+            },
+        }
+    }
 }
 
 pub fn run(geng: &Geng, level: Option<String>) -> impl geng::State {
@@ -494,7 +819,13 @@ pub fn run(geng: &Geng, level: Option<String>) -> impl geng::State {
             let assets: Rc<Assets> = geng::LoadAsset::load(&geng, &run_dir().join("assets"))
                 .await
                 .expect("Failed to load assets");
-            Editor::new(&geng, &assets, level)
+            let level_name = level.unwrap_or_else(|| "new_level.json".to_string());
+            let level = util::report_err(
+                Level::load_async(&geng, &level_name).await,
+                "Failed to load level",
+            )
+            .unwrap_or_default();
+            Editor::new(&geng, &assets, level_name, level)
         }
     };
     geng::LoadingScreen::new(geng, geng::EmptyLoadingScreen, future, |state| state)
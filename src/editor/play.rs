@@ -0,0 +1,147 @@
+use super::*;
+
+/// Minimal distance between the recorded points of the path.
+const TRACE_STEP: f32 = 0.1;
+
+/// Plays the edited level without saving it or leaving the editor.
+pub struct PlayTest {
+    world: World,
+    render: GameRender,
+    controls: game::Controls,
+    control: PlayerControl,
+    trace: PlayTrace,
+    /// Deaths already recorded in the trace, the retry key kills the player between updates.
+    recorded_deaths: usize,
+}
+
+/// The path taken and the places of the deaths during a play-test.
+#[derive(Debug, Default)]
+pub struct PlayTrace {
+    /// Path of the player, split at the deaths.
+    paths: Vec<Vec<vec2<Coord>>>,
+    deaths: Vec<vec2<Coord>>,
+}
+
+impl PlayTest {
+    pub fn new(geng: &Geng, assets: &Rc<Assets>, level: Level) -> Self {
+        let world = World::new(geng, assets, assets.rules.clone(), level);
+        let mut trace = PlayTrace::default();
+        trace.paths.push(vec![world.player.collider.pos()]);
+        Self {
+            render: GameRender::new(geng, assets),
+            controls: default(),
+            control: default(),
+            trace,
+            recorded_deaths: world.deaths,
+            world,
+        }
+    }
+
+    /// Updates the world, returns whether the finish has been reached.
+    pub fn update(&mut self, window: &geng::Window, delta_time: Time) -> bool {
+        self.controls.update(window, &mut self.control);
+        self.world.update(self.control.take(), delta_time);
+
+        let pos = self.world.player.collider.pos();
+        if self.world.deaths > self.recorded_deaths {
+            self.recorded_deaths = self.world.deaths;
+            self.trace.deaths.push(pos);
+            self.trace.paths.push(vec![]);
+        } else if !matches!(self.world.player.state, PlayerState::Respawning { .. }) {
+            let path = self
+                .trace
+                .paths
+                .last_mut()
+                .expect("the path is never empty");
+            let moved = match path.last() {
+                Some(&last) => (pos - last).len() > Coord::new(TRACE_STEP),
+                None => true,
+            };
+            if moved {
+                path.push(pos);
+            }
+        }
+
+        self.world.level_transition.take().is_some()
+    }
+
+    pub fn key_down(&mut self, key: geng::Key) {
+        self.controls
+            .key_down(key, &mut self.control, &mut self.world);
+    }
+
+    pub fn draw(&mut self, screen: &mut PixelScreen, framebuffer: &mut ugli::Framebuffer) {
+        screen.update(framebuffer.size());
+        self.world.set_screen_resolution(screen.resolution());
+        let mut pixel_framebuffer = screen.framebuffer();
+        ugli::clear(&mut pixel_framebuffer, Some(Rgba::BLACK), None, None);
+        self.render
+            .draw_world(&self.world, false, &mut pixel_framebuffer);
+        screen.draw(framebuffer);
+        self.render.draw_ui(None, &self.world, framebuffer);
+    }
+
+    pub fn camera(&self) -> &Camera2d {
+        &self.world.camera
+    }
+
+    pub fn trace(&self) -> &PlayTrace {
+        &self.trace
+    }
+
+    pub fn into_trace(self) -> PlayTrace {
+        self.trace
+    }
+}
+
+impl PlayTrace {
+    /// Draws the path and the deaths over the level.
+    pub fn draw(
+        &self,
+        geng: &Geng,
+        assets: &Assets,
+        camera: &Camera2d,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        for path in &self.paths {
+            if path.len() < 2 {
+                continue;
+            }
+            let vertices = path.iter().map(|pos| pos.map(Coord::as_f32)).collect();
+            geng.draw_2d(
+                framebuffer,
+                camera,
+                &draw_2d::Chain::new(vertices, 0.05, Rgba::new(1.0, 1.0, 1.0, 0.6), 1),
+            );
+        }
+        let texture = &assets.sprites.skull;
+        let size = texture.size().map(|x| x as f32 / PIXELS_PER_UNIT);
+        for &pos in &self.deaths {
+            let target = Aabb2::point(pos.map(Coord::as_f32)).extend_symmetric(size / 2.0);
+            geng.draw_2d(
+                framebuffer,
+                camera,
+                &draw_2d::TexturedQuad::colored(target, texture, Rgba::new(1.0, 1.0, 1.0, 0.8)),
+            );
+        }
+    }
+}
+
+impl Editor {
+    /// Starts playing the level as it is in the editor.
+    pub fn start_play(&mut self, at_cursor: bool) {
+        let mut level = self.level.clone();
+        if at_cursor {
+            level.spawn_point = self.cursor_world_pos;
+        }
+        self.dragging = None;
+        self.play = Some(PlayTest::new(&self.geng, &self.assets, level));
+    }
+
+    /// Returns to editing, keeping the trace of the play-test.
+    pub fn stop_play(&mut self) {
+        if let Some(play) = self.play.take() {
+            self.trace = Some(play.into_trace());
+        }
+    }
+}
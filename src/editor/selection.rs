@@ -0,0 +1,116 @@
+use super::*;
+
+/// Blocks copied in the editor, kept when switching between levels.
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    blocks: Vec<Block>,
+    /// Grid of the level the blocks were copied from.
+    grid: Grid,
+    /// Bottom left cell of the copied blocks, pasted at the cursor.
+    origin: vec2<isize>,
+}
+
+impl Editor {
+    /// Selects the blocks inside the area that can be hovered in the active tab,
+    /// the level tab selects everything.
+    pub fn select_area(&mut self, area: Aabb2<Coord>) {
+        let mut blocks = self.level.blocks_in(area);
+        if let Some(tab) = self.tabs.get(self.active_tab) {
+            if !tab.hoverable.is_empty() {
+                blocks.retain(|id| tab.hoverable.iter().any(|&ty| id.fits_type(ty)));
+            }
+        }
+        self.selection = blocks;
+    }
+
+    /// Checks whether the position is on one of the selected blocks.
+    pub fn selection_contains(&self, pos: vec2<Coord>) -> bool {
+        self.selection
+            .iter()
+            .any(|&id| matches!(self.level.block_bounds(id), Some(bounds) if bounds.contains(pos)))
+    }
+
+    pub fn copy_selection(&mut self) {
+        let blocks: Vec<Block> = self
+            .selection
+            .iter()
+            .filter_map(|&id| self.level.get_block(id))
+            .collect();
+        let Some(origin) = blocks
+            .iter()
+            .map(|block| self.level.block_cell(block))
+            .reduce(|a, b| vec2(a.x.min(b.x), a.y.min(b.y)))
+        else {
+            return;
+        };
+        info!("Copied {} blocks", blocks.len());
+        self.clipboard = Some(Clipboard {
+            blocks,
+            grid: self.level.grid.clone(),
+            origin,
+        });
+    }
+
+    pub fn cut_selection(&mut self) {
+        self.copy_selection();
+        self.delete_selection();
+    }
+
+    pub fn delete_selection(&mut self) {
+        if self.selection.is_empty() {
+            return;
+        }
+        let selection = std::mem::take(&mut self.selection);
+        self.action(Action::Delete(selection));
+    }
+
+    /// Pastes the clipboard with its bottom left cell at the cursor.
+    pub fn paste(&mut self) {
+        let Some(clipboard) = &self.clipboard else {
+            return;
+        };
+        let target = self.level.grid.world_to_grid(self.cursor_world_pos).0;
+        let cells = target - clipboard.origin;
+        let delta =
+            self.level.grid.grid_to_world(target) - clipboard.grid.grid_to_world(clipboard.origin);
+        let blocks = clipboard
+            .blocks
+            .iter()
+            .cloned()
+            .map(|mut block| {
+                block.translate(cells, delta);
+                block
+            })
+            .collect();
+        self.action(Action::Paste(blocks));
+    }
+
+    /// Returns the whole number of cells the selection is dragged by.
+    pub fn selection_drag_cells(&self, initial_world_pos: vec2<Coord>) -> vec2<isize> {
+        let grid = &self.level.grid;
+        grid.world_to_grid(self.cursor_world_pos).0 - grid.world_to_grid(initial_world_pos).0
+    }
+
+    /// Moves the selected blocks by the whole number of cells.
+    pub fn move_selection(&mut self, cells: vec2<isize>) {
+        if cells == vec2::ZERO || self.selection.is_empty() {
+            return;
+        }
+        let grid = &self.level.grid;
+        let delta = grid.grid_to_world(cells) - grid.grid_to_world(vec2::ZERO);
+        let blocks = self
+            .selection
+            .iter()
+            .filter_map(|&id| self.level.get_block(id))
+            .map(|mut block| {
+                block.translate(cells, delta);
+                block
+            })
+            .collect();
+        let selection = std::mem::take(&mut self.selection);
+        self.action(Action::Group(vec![
+            Action::Delete(selection),
+            Action::Paste(blocks),
+        ]));
+    }
+}
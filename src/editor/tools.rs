@@ -0,0 +1,133 @@
+use super::*;
+
+/// The way tiles are placed in the tiles tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileTool {
+    /// Paints the tiles under the cursor while dragging.
+    Brush,
+    /// Fills the region of the same tiles under the cursor.
+    Fill,
+    Rectangle,
+    RectangleOutline,
+    Line,
+}
+
+impl TileTool {
+    pub fn all() -> [Self; 5] {
+        use TileTool::*;
+        [Brush, Fill, Rectangle, RectangleOutline, Line]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Brush => "Brush",
+            Self::Fill => "Fill",
+            Self::Rectangle => "Rect",
+            Self::RectangleOutline => "Outline",
+            Self::Line => "Line",
+        }
+    }
+
+    /// Whether the tool places a shape stretched from the drag start to the cursor.
+    pub fn is_shape(&self) -> bool {
+        matches!(self, Self::Rectangle | Self::RectangleOutline | Self::Line)
+    }
+
+    /// Returns the cells of the shape between the two corner cells.
+    pub fn shape_cells(&self, from: vec2<isize>, to: vec2<isize>) -> Vec<vec2<isize>> {
+        match self {
+            Self::Brush | Self::Fill => vec![],
+            Self::Rectangle => {
+                let min = vec2(from.x.min(to.x), from.y.min(to.y));
+                let max = vec2(from.x.max(to.x), from.y.max(to.y));
+                (min.y..=max.y)
+                    .flat_map(|y| (min.x..=max.x).map(move |x| vec2(x, y)))
+                    .collect()
+            }
+            Self::RectangleOutline => {
+                let min = vec2(from.x.min(to.x), from.y.min(to.y));
+                let max = vec2(from.x.max(to.x), from.y.max(to.y));
+                Self::Rectangle
+                    .shape_cells(from, to)
+                    .into_iter()
+                    .filter(|pos| {
+                        pos.x == min.x || pos.x == max.x || pos.y == min.y || pos.y == max.y
+                    })
+                    .collect()
+            }
+            Self::Line => line_cells(from, to),
+        }
+    }
+}
+
+/// Bresenham's line between the two cells, including both ends.
+fn line_cells(from: vec2<isize>, to: vec2<isize>) -> Vec<vec2<isize>> {
+    let delta = vec2((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let step = vec2((to.x - from.x).signum(), (to.y - from.y).signum());
+    let mut error = delta.x + delta.y;
+    let mut pos = from;
+    let mut cells = vec![pos];
+    while pos != to {
+        let double = error * 2;
+        if double >= delta.y {
+            error += delta.y;
+            pos.x += step.x;
+        }
+        if double <= delta.x {
+            error += delta.x;
+            pos.y += step.y;
+        }
+        cells.push(pos);
+    }
+    cells
+}
+
+impl Editor {
+    /// Returns the tile placed by the tile tools, air when erasing.
+    pub fn tool_tile(&self, button: geng::MouseButton) -> Option<Tile> {
+        let Some(BlockType::Tile(tile)) = self.selected_block() else {
+            return None;
+        };
+        match button {
+            geng::MouseButton::Left => Some(tile),
+            geng::MouseButton::Right => Some(Tile::Air),
+            geng::MouseButton::Middle => None,
+        }
+    }
+
+    /// Fills the region under the cursor with the tile.
+    pub fn flood_fill(&mut self, tile: Tile) {
+        let pos = self.level.grid.world_to_grid(self.cursor_world_pos).0;
+        if self.level.tiles.get_tile_isize(pos) == Some(tile) {
+            return;
+        }
+        let tiles = self
+            .level
+            .tiles
+            .flood_region(pos)
+            .into_iter()
+            .map(|pos| (tile, pos))
+            .collect();
+        self.action(Action::SetTiles(tiles));
+    }
+
+    /// Returns the cells inside the level covered by the shape from the cell to the cursor.
+    pub fn shape_cells(&self, from: vec2<isize>) -> Vec<vec2<isize>> {
+        let to = self.level.grid.world_to_grid(self.cursor_world_pos).0;
+        self.tile_tool
+            .shape_cells(from, to)
+            .into_iter()
+            .filter(|&pos| self.level.tiles.get_tile_isize(pos).is_some())
+            .collect()
+    }
+
+    /// Places the shape from the cell to the cursor.
+    pub fn place_shape(&mut self, tile: Tile, from: vec2<isize>) {
+        let tiles = self
+            .shape_cells(from)
+            .into_iter()
+            .map(|pos| (tile, pos))
+            .collect();
+        self.action(Action::SetTiles(tiles));
+    }
+}
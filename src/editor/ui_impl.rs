@@ -18,6 +18,16 @@ impl Editor {
         // );
 
         let text_size = framebuffer_size.y * 0.05;
+
+        #[cfg(target_arch = "wasm32")]
+        let open_button = {
+            let button = Button::new(cx, "Open");
+            if button.was_clicked() {
+                self.upload_level();
+            }
+            button
+        };
+
         let mut level_info = geng::ui::column![
             Text::new(
                 &self.level_name,
@@ -34,6 +44,10 @@ impl Editor {
             },
         ];
 
+        #[cfg(target_arch = "wasm32")]
+        level_info
+            .extend([Box::new(open_button.padding_bottom(text_size.into())) as Box<dyn Widget>]);
+
         if let Some(tab) = self.tabs.get(self.active_tab) {
             if let EditorMode::Level = tab.mode {
                 level_info.extend([
@@ -48,14 +62,16 @@ impl Editor {
                         {
                             let inc = Button::new(cx, "+");
                             if inc.was_clicked() {
-                                self.level.change_size(self.level.size + vec2(1, 0));
+                                self.ui_actions
+                                    .push(Action::Resize(self.level.size + vec2(1, 0)));
                             }
                             inc.padding_right(text_size.into())
                         },
                         {
                             let dec = Button::new(cx, "-");
                             if dec.was_clicked() {
-                                self.level.change_size(self.level.size - vec2(1, 0));
+                                self.ui_actions
+                                    .push(Action::Resize(self.level.size - vec2(1, 0)));
                             }
                             dec.padding_right(text_size.into())
                         },
@@ -71,14 +87,16 @@ impl Editor {
                         {
                             let inc = Button::new(cx, "+");
                             if inc.was_clicked() {
-                                self.level.change_size(self.level.size + vec2(0, 1));
+                                self.ui_actions
+                                    .push(Action::Resize(self.level.size + vec2(0, 1)));
                             }
                             inc.padding_right(text_size.into())
                         },
                         {
                             let dec = Button::new(cx, "-");
                             if dec.was_clicked() {
-                                self.level.change_size(self.level.size - vec2(0, 1));
+                                self.ui_actions
+                                    .push(Action::Resize(self.level.size - vec2(0, 1)));
                             }
                             dec.padding_right(text_size.into())
                         },
@@ -100,14 +118,14 @@ impl Editor {
                         {
                             let left = Button::new(cx, "left");
                             if left.was_clicked() {
-                                self.level.translate(vec2(-1, 0));
+                                self.ui_actions.push(Action::Translate(vec2(-1, 0)));
                             }
                             left.padding_right(text_size.into())
                         },
                         {
                             let right = Button::new(cx, "right");
                             if right.was_clicked() {
-                                self.level.translate(vec2(1, 0));
+                                self.ui_actions.push(Action::Translate(vec2(1, 0)));
                             }
                             right.padding_right(text_size.into())
                         },
@@ -118,18 +136,39 @@ impl Editor {
                         {
                             let down = Button::new(cx, "down");
                             if down.was_clicked() {
-                                self.level.translate(vec2(0, -1));
+                                self.ui_actions.push(Action::Translate(vec2(0, -1)));
                             }
                             down.padding_right(text_size.into())
                         },
                         {
                             let up = Button::new(cx, "up");
                             if up.was_clicked() {
-                                self.level.translate(vec2(0, 1));
+                                self.ui_actions.push(Action::Translate(vec2(0, 1)));
                             }
                             up.padding_right(text_size.into())
                         },
                     ]),
+                    Box::new(geng::ui::row![
+                        Text::new("Grade", self.geng.default_font(), text_size, Rgba::WHITE)
+                            .padding_right(text_size.into()),
+                        {
+                            let grade =
+                                Button::new(cx, self.level.grade.as_deref().unwrap_or("default"));
+                            if grade.was_clicked() {
+                                // Cycle through the grades and back to the default one
+                                let names = self.assets.grades.names();
+                                let next = match &self.level.grade {
+                                    None => names.first(),
+                                    Some(grade) => names
+                                        .iter()
+                                        .position(|name| name == grade)
+                                        .and_then(|i| names.get(i + 1)),
+                                };
+                                self.ui_actions.push(Action::SetGrade(next.cloned()));
+                            }
+                            grade
+                        },
+                    ]),
                 ]);
             }
         }
@@ -177,6 +216,8 @@ impl Editor {
                 BlockType::Coin => (&self.assets.sprites.coin, unit),
                 BlockType::Prop(prop) => (self.assets.sprites.props.get_texture(prop), unit),
                 BlockType::Spotlight(..) => (&self.assets.sprites.spotlight, unit),
+                BlockType::PointLight(..) => (&self.assets.sprites.point_light, unit),
+                BlockType::CameraZone(..) => (&self.assets.sprites.camera, unit),
             };
             let texture_size = (uv[2] - uv[0]) * texture.size().map(|x| x as f32);
             let scale = framebuffer_size.y / 90.0;
@@ -206,73 +247,373 @@ impl Editor {
                 .uniform_padding(framebuffer_size.y as f64 * 0.05),
         ];
 
+        if let Some(BlockType::Tile(_)) = self.selected_block() {
+            let tools = TileTool::all()
+                .into_iter()
+                .map(|tool| {
+                    let color = if tool == self.tile_tool {
+                        Rgba::opaque(0.1, 0.1, 0.3)
+                    } else {
+                        Rgba::GRAY
+                    };
+                    let button = Button::new(cx, tool.name());
+                    if button.was_clicked() {
+                        self.tile_tool = tool;
+                    }
+                    Box::new(
+                        geng::ui::stack![geng::ui::ColorBox::new(color), button]
+                            .padding_right(framebuffer_size.x as f64 * 0.01),
+                    ) as Box<dyn geng::ui::Widget>
+                })
+                .collect();
+            stack.push(Box::new(
+                geng::ui::row(tools)
+                    .align(vec2(0.0, 0.0))
+                    .uniform_padding(framebuffer_size.y as f64 * 0.05),
+            ));
+        }
+
         let text_size = framebuffer_size.y * 0.03;
         let font = &self.assets.font;
+        // Set by the sliders, tells whether the inspected values have been changed
+        let changed = std::cell::Cell::new(false);
+        let slider_dragged = std::cell::Cell::new(false);
         let slider = |name, range, value: &mut f32| {
             let slider = ui::Slider::new(cx, (*value).into(), range);
             if let Some(change) = slider.get_change() {
                 *value = change as f32;
+                changed.set(true);
+            }
+            if slider.is_captured() {
+                slider_dragged.set(true);
             }
             geng::ui::row![
                 geng::ui::Text::new(name, font, text_size, Rgba::WHITE),
                 slider
             ]
         };
+        let coord_slider = |name, range, value: &mut Coord| {
+            let mut v = value.as_f32();
+            let slider = slider(name, range, &mut v);
+            *value = Coord::new(v);
+            slider
+        };
+        let color = |value: &mut Rgba<f32>| {
+            geng::ui::column![
+                slider("Red", 0.0..=1.0, &mut value.r),
+                slider("Green", 0.0..=1.0, &mut value.g),
+                slider("Blue", 0.0..=1.0, &mut value.b),
+            ]
+        };
+        let panel = |widgets: Vec<Box<dyn Widget>>, height: f64| {
+            geng::ui::stack![
+                geng::ui::ColorBox::new(Rgba::new(0.0, 0.0, 0.0, 0.5)),
+                geng::ui::column(widgets),
+            ]
+            .fixed_size(framebuffer_size.map(|x| x as f64) * vec2(0.2, height))
+            .align(vec2(1.0, 0.5))
+            .uniform_padding(framebuffer_size.x as f64 * 0.05)
+        };
+
+        // Inspector of the selected block
+        let bounds = self.level.bounds().map(|x| x.as_f32() as f64);
+        if let Some(block) = &mut self.inspected {
+            let mut widgets: Vec<Box<dyn Widget>> = Vec::new();
+            let name = match block {
+                Block::Tile(_) => "Tile",
+                Block::Hazard(_) => "Hazard",
+                Block::Prop(_) => "Prop",
+                Block::Coin(_) => "Coin",
+                Block::Spotlight(_) => "Spotlight",
+                Block::PointLight(_) => "Point light",
+                Block::CameraZone(_) => "Camera zone",
+            };
+            widgets.push(Box::new(
+                geng::ui::Text::new(name, font, text_size * 1.5, Rgba::WHITE)
+                    .padding_bottom(text_size.into()),
+            ));
 
-        if let Some(tab) = &mut self.tabs.get_mut(self.active_tab) {
-            if let EditorMode::Lights = &mut tab.mode {
-                if let Some(config) = self.selected_block.and_then(|id| {
-                    if let BlockId::Spotlight(id) = id {
-                        self.level.spotlights.get_mut(id)
+            if !matches!(block, Block::Tile(_)) {
+                let mut pos = block.position();
+                widgets.push(Box::new(coord_slider(
+                    "X",
+                    bounds.min.x..=bounds.max.x,
+                    &mut pos.x,
+                )));
+                widgets.push(Box::new(coord_slider(
+                    "Y",
+                    bounds.min.y..=bounds.max.y,
+                    &mut pos.y,
+                )));
+                block.set_position(pos);
+            }
+
+            match block {
+                Block::Tile(_) | Block::Coin(_) => {}
+                Block::Hazard(hazard) => {
+                    let all = HazardType::all();
+                    let hazard_type = Button::new(cx, &format!("Type: {:?}", hazard.hazard_type));
+                    let direction = hazard.direction.unwrap_or(vec2(Coord::ZERO, -Coord::ONE));
+                    let direction_name = if direction.x > Coord::ZERO {
+                        "right"
+                    } else if direction.x < Coord::ZERO {
+                        "left"
+                    } else if direction.y > Coord::ZERO {
+                        "up"
                     } else {
-                        None
+                        "down"
+                    };
+                    let rotate = Button::new(cx, &format!("Direction: {direction_name}"));
+                    if hazard_type.was_clicked() {
+                        let i = all
+                            .iter()
+                            .position(|&t| t == hazard.hazard_type)
+                            .unwrap_or(0);
+                        hazard.hazard_type = all[(i + 1) % all.len()];
+                        self.level.orient_hazard(hazard, direction);
+                        changed.set(true);
+                    }
+                    if rotate.was_clicked() {
+                        // Counter-clockwise
+                        self.level
+                            .orient_hazard(hazard, vec2(-direction.y, direction.x));
+                        changed.set(true);
                     }
-                }) {
-                    // Spotlight
-                    let angle = slider("Direction", 0.0..=f64::PI * 2.0, &mut config.angle);
-                    let angle_range = slider("Angle", 0.0..=f64::PI * 2.0, &mut config.angle_range);
-                    let color = geng::ui::Void; // TODO
-                    let intensity = slider("Intensity", 0.0..=1.0, &mut config.intensity);
-                    let max_distance = {
-                        let mut d = config.max_distance.as_f32();
-                        let slider = slider("Distance", 0.0..=50.0, &mut d);
-                        config.max_distance = Coord::new(d);
-                        slider
+                    widgets.push(Box::new(hazard_type));
+                    widgets.push(Box::new(rotate));
+                }
+                Block::Prop(prop) => {
+                    let prop_type = Button::new(cx, &format!("Type: {:?}", prop.prop_type));
+                    if prop_type.was_clicked() {
+                        let all = PropType::all();
+                        let i = all.iter().position(|&t| t == prop.prop_type).unwrap_or(0);
+                        prop.prop_type = all[(i + 1) % all.len()];
+                        // Fit the sprite to the new texture
+                        let size = self
+                            .assets
+                            .sprites
+                            .props
+                            .get_texture(&prop.prop_type)
+                            .size()
+                            .map(|x| Coord::new(x as f32 / PIXELS_PER_UNIT));
+                        prop.sprite = Aabb2::point(prop.sprite.center())
+                            .extend_symmetric(size / Coord::new(2.0));
+                        changed.set(true);
+                    }
+                    widgets.push(Box::new(prop_type));
+                }
+                Block::Spotlight(config) => {
+                    let falloff = Button::new(cx, &format!("Falloff: {:?}", config.falloff));
+                    if falloff.was_clicked() {
+                        let all = LightFalloff::all();
+                        let i = all.iter().position(|&f| f == config.falloff).unwrap_or(0);
+                        config.falloff = all[(i + 1) % all.len()];
+                        changed.set(true);
+                    }
+                    widgets.extend([
+                        Box::new(slider("Direction", 0.0..=f64::PI * 2.0, &mut config.angle))
+                            as Box<dyn Widget>,
+                        Box::new(slider(
+                            "Angle",
+                            0.0..=f64::PI * 2.0,
+                            &mut config.angle_range,
+                        )),
+                        Box::new(slider(
+                            "Edge softness",
+                            0.0..=1.0,
+                            &mut config.angle_softness,
+                        )),
+                        Box::new(color(&mut config.color)),
+                        Box::new(slider("Intensity", 0.0..=1.0, &mut config.intensity)),
+                        Box::new(coord_slider(
+                            "Distance",
+                            0.0..=50.0,
+                            &mut config.max_distance,
+                        )),
+                        Box::new(falloff),
+                        Box::new(coord_slider(
+                            "Source size",
+                            0.0..=2.0,
+                            &mut config.source_radius,
+                        )),
+                        Box::new(slider("Volume", 0.0..=1.0, &mut config.volume)),
+                    ]);
+                }
+                Block::PointLight(config) => {
+                    let falloff = Button::new(cx, &format!("Falloff: {:?}", config.falloff));
+                    if falloff.was_clicked() {
+                        let all = LightFalloff::all();
+                        let i = all.iter().position(|&f| f == config.falloff).unwrap_or(0);
+                        config.falloff = all[(i + 1) % all.len()];
+                        changed.set(true);
+                    }
+                    let animation = &mut config.animation;
+                    widgets.extend([
+                        Box::new(color(&mut config.color)) as Box<dyn Widget>,
+                        Box::new(slider("Intensity", 0.0..=1.0, &mut config.intensity)),
+                        Box::new(coord_slider(
+                            "Distance",
+                            0.0..=50.0,
+                            &mut config.max_distance,
+                        )),
+                        Box::new(falloff),
+                        Box::new(coord_slider(
+                            "Source size",
+                            0.0..=2.0,
+                            &mut config.source_radius,
+                        )),
+                        Box::new(slider("Volume", 0.0..=1.0, &mut config.volume)),
+                        Box::new(slider("Flicker", 0.0..=1.0, &mut animation.flicker)),
+                        Box::new(slider("Pulse", 0.0..=1.0, &mut animation.pulse)),
+                        Box::new(slider(
+                            "Pulse period",
+                            0.1..=10.0,
+                            &mut animation.pulse_period,
+                        )),
+                    ]);
+                }
+                Block::CameraZone(zone) => {
+                    let center = zone.area.center();
+                    let mut size = zone.area.size();
+                    widgets.push(Box::new(coord_slider("Width", 1.0..=100.0, &mut size.x)));
+                    widgets.push(Box::new(coord_slider("Height", 1.0..=100.0, &mut size.y)));
+                    zone.area = Aabb2::point(center).extend_symmetric(size / Coord::new(2.0));
+
+                    let zoom_text = if zone.fov.is_some() {
+                        "reset zoom"
+                    } else {
+                        "zoom"
                     };
-                    let volume = slider("Volume", 0.0..=1.0, &mut config.volume);
+                    let zoom = Button::new(cx, zoom_text);
+                    if zoom.was_clicked() {
+                        zone.fov = match zone.fov {
+                            Some(_) => None,
+                            None => Some(DEFAULT_FOV),
+                        };
+                        changed.set(true);
+                    }
+                    widgets.push(Box::new(zoom));
+                    if let Some(fov) = &mut zone.fov {
+                        widgets.push(Box::new(slider("View", 5.0..=50.0, fov)));
+                    }
+                }
+            }
+            self.inspected_changed |= changed.replace(false);
+            stack.push(Box::new(panel(widgets, 0.7)));
+        } else if let Some(EditorMode::Lights { .. }) =
+            self.tabs.get(self.active_tab).map(|tab| &tab.mode)
+        {
+            // Global light
+            let mut light = self.level.global_light;
+            let widgets = vec![
+                Box::new(
+                    geng::ui::Text::new("Global light", font, text_size * 1.5, Rgba::WHITE)
+                        .padding_bottom(text_size.into()),
+                ) as Box<dyn Widget>,
+                Box::new(color(&mut light.color)),
+                Box::new(slider("Intensity", 0.0..=1.0, &mut light.intensity)),
+            ];
+            if changed.replace(false) {
+                self.ui_actions.push(Action::SetGlobalLight(light));
+            }
+            stack.push(Box::new(panel(widgets, 0.4)));
+        }
 
-                    let light = geng::ui::stack![
-                        geng::ui::ColorBox::new(Rgba::new(0.0, 0.0, 0.0, 0.5)),
-                        geng::ui::column![
-                            angle,
-                            angle_range,
-                            color,
-                            intensity,
-                            max_distance,
-                            volume
-                        ]
-                    ]
-                    .fixed_size(framebuffer_size.map(|x| x as f64) * vec2(0.2, 0.5))
-                    .align(vec2(1.0, 0.5))
-                    .uniform_padding(framebuffer_size.x as f64 * 0.05);
-                    stack.push(Box::new(light));
-                } else {
-                    // Global light
-                    let config = &mut self.level.global_light;
-                    let color = geng::ui::Void; // TODO
-                    let intensity = slider("Intensity", 0.0..=1.0, &mut config.intensity);
+        if let Some(EditorMode::Level) = self.tabs.get(self.active_tab).map(|tab| &tab.mode) {
+            // Rule overrides
+            let mut patch = self.level.rules.clone();
+            let rule = |name, range, default: f32, value: &mut Option<f32>| {
+                let toggle = Button::new(cx, if value.is_some() { "reset" } else { "edit" });
+                if toggle.was_clicked() {
+                    *value = match value {
+                        Some(_) => None,
+                        None => Some(default),
+                    };
+                    changed.set(true);
+                }
+                let value: Box<dyn Widget> = match value {
+                    Some(value) => Box::new(slider(name, range, value)),
+                    None => Box::new(geng::ui::Text::new(
+                        format!("{name}: {default:.1}"),
+                        font,
+                        text_size,
+                        Rgba::GRAY,
+                    )),
+                };
+                geng::ui::row![toggle.padding_right(text_size.into()), value]
+            };
+            macro_rules! coord_rule {
+                ($name:expr, $field:ident, $range:expr) => {{
+                    let mut value = patch.$field.map(Coord::as_f32);
+                    let default = self.assets.rules.$field.as_f32();
+                    let widget = rule($name, $range, default, &mut value);
+                    patch.$field = value.map(Coord::new);
+                    widget
+                }};
+            }
 
-                    let light = geng::ui::stack![
-                        geng::ui::ColorBox::new(Rgba::new(0.0, 0.0, 0.0, 0.5)),
-                        geng::ui::column![color, intensity],
-                    ]
-                    .fixed_size(framebuffer_size.map(|x| x as f64) * vec2(0.2, 0.5))
-                    .align(vec2(1.0, 0.5))
-                    .uniform_padding(framebuffer_size.x as f64 * 0.05);
-                    stack.push(Box::new(light));
+            let gravity = {
+                let default = self.assets.rules.gravity;
+                let mut value = patch.gravity.map(|gravity| gravity.y.as_f32());
+                let widget = rule("Gravity", -60.0..=0.0, default.y.as_f32(), &mut value);
+                patch.gravity = value.map(|y| vec2(default.x, Coord::new(y)));
+                widget
+            };
+            let move_speed = coord_rule!("Move speed", move_speed, 0.0..=20.0);
+            let jump = coord_rule!("Jump", normal_jump_strength, 0.0..=30.0);
+            let wall_jump = coord_rule!("Wall jump", wall_jump_strength, 0.0..=30.0);
+            let free_fall = coord_rule!("Fall speed", free_fall_speed, 0.0..=40.0);
+            let drill_speed = coord_rule!("Drill speed", drill_speed_min, 0.0..=40.0);
+            let dash_speed = coord_rule!("Dash speed", drill_dash_speed_min, 0.0..=40.0);
+            let drill_jump = coord_rule!("Drill jump", drill_jump_speed_min, 0.0..=40.0);
+            let drill_dash = {
+                let value = patch.can_drill_dash;
+                let button = Button::new(
+                    cx,
+                    match value {
+                        None => "default",
+                        Some(true) => "on",
+                        Some(false) => "off",
+                    },
+                );
+                if button.was_clicked() {
+                    patch.can_drill_dash = match value {
+                        None => Some(true),
+                        Some(true) => Some(false),
+                        Some(false) => None,
+                    };
+                    changed.set(true);
                 }
+                geng::ui::row![
+                    geng::ui::Text::new("Drill dash", font, text_size, Rgba::WHITE)
+                        .padding_right(text_size.into()),
+                    button,
+                ]
+            };
+
+            let rules = geng::ui::stack![
+                geng::ui::ColorBox::new(Rgba::new(0.0, 0.0, 0.0, 0.5)),
+                geng::ui::column![
+                    gravity,
+                    move_speed,
+                    jump,
+                    wall_jump,
+                    free_fall,
+                    drill_dash,
+                    drill_speed,
+                    dash_speed,
+                    drill_jump,
+                ],
+            ]
+            .fixed_size(framebuffer_size.map(|x| x as f64) * vec2(0.25, 0.6))
+            .align(vec2(0.0, 0.5))
+            .uniform_padding(framebuffer_size.x as f64 * 0.02);
+            stack.push(Box::new(rules));
+            if changed.replace(false) {
+                self.ui_actions.push(Action::SetRules(patch));
             }
         }
+        self.ui_dragging = slider_dragged.get();
 
         Box::new(stack)
     }
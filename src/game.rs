@@ -5,7 +5,10 @@ pub struct Game {
     assets: Rc<Assets>,
     render: GameRender,
     framebuffer_size: vec2<usize>,
-    pixel_texture: ugli::Texture,
+    screen: PixelScreen,
+    pack: Rc<LevelPack>,
+    /// Global rules without the level overrides.
+    rules: Rules,
     level_name: String,
     world: World,
     draw_hitboxes: bool,
@@ -17,9 +20,17 @@ pub struct Game {
     show_time: bool,
     music: Option<geng::SoundEffect>,
     show_debug: bool,
+    /// Whether the assist menu is open. The game is paused while it is.
+    show_assists: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    watcher: Option<hot_reload::AssetsWatcher>,
+    /// Assets being reloaded after a tileset change.
+    #[cfg(not(target_arch = "wasm32"))]
+    assets_reload: Option<geng::AssetFuture<Assets>>,
 }
 
-struct Controls {
+/// Keys controlling the player.
+pub struct Controls {
     left: Vec<geng::Key>,
     right: Vec<geng::Key>,
     down: Vec<geng::Key>,
@@ -29,88 +40,188 @@ struct Controls {
     retry: Vec<geng::Key>,
 }
 
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            left: vec![geng::Key::Left],
+            right: vec![geng::Key::Right],
+            down: vec![geng::Key::Down],
+            up: vec![geng::Key::Up],
+            jump: vec![geng::Key::Z, geng::Key::Space],
+            drill: vec![geng::Key::C],
+            retry: vec![geng::Key::R],
+        }
+    }
+}
+
+impl Controls {
+    /// Updates the held controls from the keys pressed in the window.
+    pub fn update(&self, window: &geng::Window, control: &mut PlayerControl) {
+        let pressed = |keys: &[geng::Key]| keys.iter().any(|&key| window.is_key_pressed(key));
+
+        if pressed(&self.jump) {
+            control.hold_jump = true;
+        }
+
+        if pressed(&self.drill) {
+            control.hold_drill = true;
+        }
+
+        let mut dir = vec2::ZERO;
+        if pressed(&self.left) {
+            dir.x -= Coord::ONE;
+        }
+        if pressed(&self.right) {
+            dir.x += Coord::ONE;
+        }
+        if pressed(&self.down) {
+            dir.y -= Coord::ONE;
+        }
+        if pressed(&self.up) {
+            dir.y += Coord::ONE;
+        }
+        control.move_dir = dir;
+    }
+
+    /// Handles the key press, kills the player on retry.
+    pub fn key_down(&self, key: geng::Key, control: &mut PlayerControl, world: &mut World) {
+        if self.jump.contains(&key) {
+            control.jump = true;
+        }
+        if self.drill.contains(&key) {
+            control.drill = true;
+        }
+        if self.retry.contains(&key) {
+            world.kill_player();
+        }
+    }
+}
+
 impl Game {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         geng: &Geng,
         assets: &Rc<Assets>,
+        pack: Rc<LevelPack>,
         level_name: String,
         level: Level,
         coins: usize,
         time: Time,
         deaths: usize,
         show_time: bool,
+        assists: Assists,
+        juice: JuiceSettings,
+        mixer: Mixer,
         music: Option<geng::SoundEffect>,
     ) -> Self {
         geng.window().set_cursor_type(geng::CursorType::None);
 
         let mut world = World::new(geng, assets, assets.rules.clone(), level);
         world.coins_collected = coins;
+        world.assists = assists;
+        world.juice.settings = juice;
+        world.mixer = mixer;
         let mut music = music.unwrap_or_else(|| assets.music.play());
-        music.set_volume((world.volume - 0.3).max(0.0));
+        music.set_volume(mixer.music_volume());
         Self {
             geng: geng.clone(),
             assets: assets.clone(),
             render: GameRender::new(geng, assets),
             framebuffer_size: vec2(1, 1),
-            pixel_texture: {
-                let mut texture =
-                    ugli::Texture::new_with(geng.ugli(), SCREEN_RESOLUTION, |_| Rgba::BLACK);
-                texture.set_filter(ugli::Filter::Nearest);
-                texture
-            },
+            screen: PixelScreen::new(geng, &assets.screen),
             draw_hitboxes: false,
             show_debug: false,
+            show_assists: false,
             fade: Time::ONE,
             control: PlayerControl::default(),
-            controls: Controls {
-                left: vec![geng::Key::Left],
-                right: vec![geng::Key::Right],
-                down: vec![geng::Key::Down],
-                up: vec![geng::Key::Up],
-                jump: vec![geng::Key::Z, geng::Key::Space],
-                drill: vec![geng::Key::C],
-                retry: vec![geng::Key::R],
-            },
+            controls: Controls::default(),
             accumulated_time: time,
             music: Some(music),
             deaths,
+            pack,
+            rules: assets.rules.clone(),
             level_name,
             show_time,
             world,
+            #[cfg(not(target_arch = "wasm32"))]
+            watcher: util::report_err(
+                hot_reload::AssetsWatcher::new(),
+                "Failed to watch the assets",
+            )
+            .ok(),
+            #[cfg(not(target_arch = "wasm32"))]
+            assets_reload: None,
         }
     }
 
-    fn update_control(&mut self) {
-        macro_rules! pressed {
-            ($keys:expr) => {{
-                let window = self.geng.window();
-                $keys.iter().any(|&key| window.is_key_pressed(key))
-            }};
-        }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn hot_reload(&mut self) {
+        use hot_reload::AssetChange;
 
-        if pressed!(self.controls.jump) {
-            self.control.hold_jump = true;
+        let changes = self
+            .watcher
+            .as_ref()
+            .map(|watcher| watcher.changes())
+            .unwrap_or_default();
+        for change in changes {
+            match change {
+                AssetChange::Rules => self.reload_rules(),
+                AssetChange::Level(name) => {
+                    if name == self.level_name {
+                        self.reload_level();
+                    }
+                }
+                AssetChange::TileSet | AssetChange::Particles | AssetChange::Post => {
+                    info!("Reloading assets");
+                    self.assets_reload =
+                        Some(geng::LoadAsset::load(&self.geng, &run_dir().join("assets")));
+                }
+            }
         }
 
-        if pressed!(self.controls.drill) {
-            self.control.hold_drill = true;
+        let reloaded = self
+            .assets_reload
+            .as_mut()
+            .and_then(|future| future.now_or_never());
+        if let Some(result) = reloaded {
+            self.assets_reload = None;
+            if let Ok(assets) = util::report_err(result, "Failed to reload assets") {
+                let assets = Rc::new(assets);
+                self.render = GameRender::new(&self.geng, &assets);
+                self.screen = PixelScreen::new(&self.geng, &assets.screen);
+                self.world.set_assets(&self.geng, &assets);
+                self.assets = assets;
+                info!("Reloaded assets");
+            }
         }
+    }
 
-        let mut dir = vec2::ZERO;
-        if pressed!(self.controls.left) {
-            dir.x -= Coord::ONE;
-        }
-        if pressed!(self.controls.right) {
-            dir.x += Coord::ONE;
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_rules(&mut self) {
+        if let Ok(rules) = util::report_err(Rules::load(), "Failed to reload the rules") {
+            self.rules = rules.clone();
+            self.world.set_rules(rules);
+            info!("Reloaded the rules");
         }
-        if pressed!(self.controls.down) {
-            dir.y -= Coord::ONE;
-        }
-        if pressed!(self.controls.up) {
-            dir.y += Coord::ONE;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_level(&mut self) {
+        let level = Level::load(&self.level_name);
+        if let Ok(level) = util::report_err(level, "Failed to reload the level") {
+            self.world.set_level(&self.geng, level);
+            self.world.set_rules(self.rules.clone());
+            info!("Reloaded the level");
         }
-        self.control.move_dir = dir;
+    }
+
+    fn toggle_assists(&mut self) {
+        self.show_assists = !self.show_assists;
+        self.geng.window().set_cursor_type(if self.show_assists {
+            geng::CursorType::Default
+        } else {
+            geng::CursorType::None
+        });
     }
 }
 
@@ -121,21 +232,15 @@ impl geng::State for Game {
         ugli::clear(framebuffer, Some(Rgba::BLACK), None, None);
 
         // Render the game onto the texture
-        let mut pixel_framebuffer = ugli::Framebuffer::new_color(
-            self.geng.ugli(),
-            ugli::ColorAttachment::Texture(&mut self.pixel_texture),
-        );
+        self.screen.update(framebuffer.size());
+        self.world.set_screen_resolution(self.screen.resolution());
+        let mut pixel_framebuffer = self.screen.framebuffer();
         ugli::clear(&mut pixel_framebuffer, Some(Rgba::BLACK), None, None);
         self.render
             .draw_world(&self.world, self.draw_hitboxes, &mut pixel_framebuffer);
 
         // Render background
-        let reference_size = vec2(16.0, 9.0);
-        let ratio = framebuffer.size().map(|x| x as f32) / reference_size;
-        let ratio = ratio.x.min(ratio.y);
-        let target_size = reference_size * ratio;
-        let screen = Aabb2::point(framebuffer.size().map(|x| x as f32) / 2.0)
-            .extend_symmetric(target_size / 2.0);
+        let screen = self.screen.target();
         self.geng.draw_2d(
             framebuffer,
             &geng::PixelPerfectCamera,
@@ -148,14 +253,13 @@ impl geng::State for Game {
         //     screen.size() * vec2(163.0, 180.0 - 47.0) / vec2(320.0, 180.0),
         // )
         // .translate(screen.bottom_left());
-        let target = screen;
-        self.geng.draw_2d(
-            framebuffer,
-            &geng::PixelPerfectCamera,
-            &draw_2d::TexturedQuad::new(target, &self.pixel_texture),
-        );
+        if self.assets.post.crt.enabled {
+            self.screen.draw_crt(framebuffer, &self.assets);
+        } else {
+            self.screen.draw(framebuffer);
+        }
 
-        let is_credits = self.level_name == "credits.json";
+        let is_credits = self.pack.is_credits(&self.level_name);
         if !is_credits {
             let show_time = self
                 .show_time
@@ -233,6 +337,16 @@ impl geng::State for Game {
                 .align_bounding_box(vec2(0.5, 1.0))
                 .translate(center),
             );
+            if self.world.assists.used {
+                self.geng.draw_2d(
+                    framebuffer,
+                    &geng::PixelPerfectCamera,
+                    &draw_2d::Text::unit(&*self.assets.font, "Assists used", Rgba::BLACK)
+                        .scale_uniform(size * 0.7)
+                        .align_bounding_box(vec2(0.5, 1.0))
+                        .translate(center - vec2(0.0, size * 3.0)),
+                );
+            }
         }
 
         if self.show_debug {
@@ -293,26 +407,30 @@ impl geng::State for Game {
         if self.fade > Time::ZERO {
             self.fade -= delta_time;
         }
+
+        if let Some(music) = &mut self.music {
+            music.set_volume(self.world.mixer.music_volume());
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.hot_reload();
     }
 
     fn fixed_update(&mut self, delta_time: f64) {
-        let delta_time = Time::new(delta_time as f32);
-        self.update_control();
+        if self.show_assists {
+            return;
+        }
+
+        let delta_time = Time::new(delta_time as f32 * self.world.assists.game_speed);
+        self.controls.update(self.geng.window(), &mut self.control);
         let control = self.control.take();
         self.world.update(control, delta_time);
     }
 
     fn handle_event(&mut self, event: geng::Event) {
         if let geng::Event::KeyDown { key } = event {
-            if self.controls.jump.contains(&key) {
-                self.control.jump = true;
-            }
-            if self.controls.drill.contains(&key) {
-                self.control.drill = true;
-            }
-            if self.controls.retry.contains(&key) {
-                self.world.kill_player();
-            }
+            self.controls
+                .key_down(key, &mut self.control, &mut self.world);
             match key {
                 geng::Key::F1 => {
                     self.draw_hitboxes = !self.draw_hitboxes;
@@ -320,6 +438,9 @@ impl geng::State for Game {
                 geng::Key::F2 => {
                     self.show_time = !self.show_time;
                 }
+                geng::Key::F3 | geng::Key::Escape => {
+                    self.toggle_assists();
+                }
                 geng::Key::F4 => {
                     self.show_debug = !self.show_debug;
                 }
@@ -328,28 +449,159 @@ impl geng::State for Game {
         }
     }
 
+    fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        use geng::ui::*;
+
+        if !self.show_assists {
+            return Box::new(Void);
+        }
+
+        let framebuffer_size = self.framebuffer_size.map(|x| x as f32);
+        let text_size = framebuffer_size.y * 0.04;
+        let font = &self.assets.font;
+        let assists = &mut self.world.assists;
+
+        let game_speed = {
+            let slider = ui::Slider::new(
+                cx,
+                assists.game_speed.into(),
+                Assists::MIN_GAME_SPEED.into()..=1.0,
+            );
+            if let Some(change) = slider.get_change() {
+                assists.game_speed = change as f32;
+            }
+            geng::ui::row![
+                Text::new(
+                    format!("Game speed: {:.0}%", assists.game_speed * 100.0),
+                    font,
+                    text_size,
+                    Rgba::WHITE
+                )
+                .padding_right(text_size.into()),
+                slider,
+            ]
+        };
+        let toggle = |name: &str, value: &mut bool| {
+            let text = format!("{name}: {}", if *value { "on" } else { "off" });
+            let button = Button::new(cx, &text);
+            if button.was_clicked() {
+                *value = !*value;
+            }
+            button.padding_bottom(text_size.into())
+        };
+        let invincible = toggle("Invincibility", &mut assists.invincible);
+        let drill_dash = toggle("Infinite drill dash", &mut assists.infinite_drill_dash);
+
+        let juice = &mut self.world.juice.settings;
+        let effects = toggle("Screen effects", &mut juice.enabled);
+        let intensity = {
+            let slider = ui::Slider::new(cx, juice.intensity.into(), 0.0..=1.0);
+            if let Some(change) = slider.get_change() {
+                juice.intensity = change as f32;
+            }
+            geng::ui::row![
+                Text::new(
+                    format!("Effects intensity: {:.0}%", juice.intensity * 100.0),
+                    font,
+                    text_size,
+                    Rgba::WHITE
+                )
+                .padding_right(text_size.into()),
+                slider,
+            ]
+        };
+
+        let mixer = &mut self.world.mixer;
+        let volume = |name: &str, value: &mut f64| {
+            let slider = ui::Slider::new(cx, *value, 0.0..=1.0);
+            if let Some(change) = slider.get_change() {
+                *value = change;
+            }
+            geng::ui::row![
+                Text::new(
+                    format!("{name}: {:.0}%", *value * 100.0),
+                    font,
+                    text_size,
+                    Rgba::WHITE
+                )
+                .padding_right(text_size.into()),
+                slider,
+            ]
+        };
+        let master = volume("Master volume", &mut mixer.master);
+        let music = volume("Music volume", &mut mixer.music);
+        let sfx = volume("Sound volume", &mut mixer.sfx);
+
+        let menu = geng::ui::stack![
+            ColorBox::new(Rgba::new(0.0, 0.0, 0.0, 0.7)),
+            geng::ui::column![
+                Text::new("Assists", font, text_size * 1.5, Rgba::WHITE)
+                    .padding_bottom(text_size.into()),
+                game_speed.padding_bottom(text_size.into()),
+                invincible,
+                drill_dash,
+                Text::new(
+                    "Runs with assists are marked in the stats",
+                    font,
+                    text_size * 0.7,
+                    Rgba::GRAY
+                )
+                .padding_bottom(text_size.into()),
+                effects,
+                intensity.padding_bottom(text_size.into()),
+                master,
+                music,
+                sfx,
+            ]
+            .uniform_padding(text_size.into()),
+        ]
+        .fixed_size(framebuffer_size.map(|x| x as f64) * vec2(0.5, 0.9))
+        .align(vec2(0.5, 0.5));
+        Box::new(menu)
+    }
+
     fn transition(&mut self) -> Option<geng::Transition> {
-        if let Some(level) = self.world.level_transition.take() {
+        if let Some(transition) = self.world.level_transition.take() {
+            let level = match transition {
+                LevelTransition::Level(level) => level,
+                LevelTransition::Next => self
+                    .pack
+                    .next_level(&self.level_name)
+                    .unwrap_or(&self.level_name[..])
+                    .to_owned(),
+            };
             if level == self.level_name {
                 let coins = self.world.coins_collected;
+                let level_coins = self.world.level_coins;
+                let assists = self.world.assists;
+                let juice = self.world.juice.settings;
+                let mixer = self.world.mixer;
                 self.world = World::new(
                     &self.geng,
                     &self.assets,
-                    self.assets.rules.clone(),
+                    self.rules.clone(),
                     self.world.level.clone(),
                 );
                 self.world.coins_collected = coins;
+                self.world.level_coins = level_coins;
+                self.world.assists = assists;
+                self.world.juice.settings = juice;
+                self.world.mixer = mixer;
                 return None;
             }
 
             return Some(geng::Transition::Switch(Box::new(game::level_change(
                 &self.geng,
                 Some(&self.assets),
+                &self.pack,
                 level,
                 self.world.coins_collected,
                 self.accumulated_time + self.world.time,
                 self.deaths + self.world.deaths,
                 self.show_time,
+                self.world.assists,
+                self.world.juice.settings,
+                self.world.mixer,
                 self.music.take(),
             ))));
         }
@@ -357,45 +609,128 @@ impl geng::State for Game {
     }
 }
 
+/// The level pack played when no other pack is specified.
+pub const DEFAULT_PACK: &str = "main.json";
+
+/// Plays through the level pack starting from the given level,
+/// or from the first level of the pack if none is given.
+/// Without a pack, the level is played in the pack that contains it.
 pub fn run(
     geng: &Geng,
     assets: Option<&Rc<Assets>>,
-    level: impl AsRef<std::path::Path>,
+    pack: Option<&str>,
+    level: Option<String>,
 ) -> impl geng::State {
-    level_change(geng, assets, level, 0, Time::ZERO, 0, false, None)
+    let future = {
+        let geng = geng.clone();
+        let assets = assets.cloned();
+        let pack = pack.map(|pack| pack.to_owned());
+        async move {
+            let assets = load_assets(&geng, assets).await;
+            let pack = match pack {
+                Some(pack) => pack,
+                None => find_pack(&geng, level.as_deref()).await,
+            };
+            let pack: LevelPack =
+                geng::LoadAsset::load(&geng, &run_dir().join("assets").join("packs").join(pack))
+                    .await
+                    .expect("Failed to load the level pack");
+            let level_name = level
+                .or_else(|| pack.first_level().map(|level| level.to_owned()))
+                .expect("The level pack is empty");
+            let level = load_level(&geng, &level_name).await;
+            Game::new(
+                &geng,
+                &assets,
+                Rc::new(pack),
+                level_name,
+                level,
+                0,
+                Time::ZERO,
+                0,
+                false,
+                Assists::default(),
+                JuiceSettings::default(),
+                Mixer::default(),
+                None,
+            )
+        }
+    };
+    geng::LoadingScreen::new(geng, geng::EmptyLoadingScreen, future, |state| state)
+}
+
+/// Returns the first pack listed in `packs.json` that contains the level,
+/// or the default pack.
+async fn find_pack(geng: &Geng, level: Option<&str>) -> String {
+    let Some(level) = level else {
+        return DEFAULT_PACK.to_owned();
+    };
+    let dir = run_dir().join("assets").join("packs");
+    let list = <String as geng::LoadAsset>::load(geng, &dir.join("packs.json")).await;
+    let Ok(list) = util::report_err(list, "Failed to load the list of packs") else {
+        return DEFAULT_PACK.to_owned();
+    };
+    let Ok(names) = util::report_err(
+        serde_json::from_str::<Vec<String>>(&list),
+        "Failed to parse the list of packs",
+    ) else {
+        return DEFAULT_PACK.to_owned();
+    };
+    for name in names {
+        let pack = <LevelPack as geng::LoadAsset>::load(geng, &dir.join(&name)).await;
+        if let Ok(pack) = util::report_err(pack, format!("Failed to load the pack {name}")) {
+            if pack.contains(level) {
+                return name;
+            }
+        }
+    }
+    DEFAULT_PACK.to_owned()
 }
 
 #[allow(clippy::too_many_arguments)]
 fn level_change(
     geng: &Geng,
     assets: Option<&Rc<Assets>>,
+    pack: &Rc<LevelPack>,
     level: impl AsRef<std::path::Path>,
     coins: usize,
     time: Time,
     deaths: usize,
     show_time: bool,
+    assists: Assists,
+    juice: JuiceSettings,
+    mixer: Mixer,
     music: Option<geng::SoundEffect>,
 ) -> impl geng::State {
     let future = {
         let geng = geng.clone();
         let assets = assets.cloned();
+        let pack = pack.clone();
         let level = level.as_ref().to_owned();
         async move {
-            let assets = match assets {
-                Some(assets) => assets,
-                None => geng::LoadAsset::load(&geng, &run_dir().join("assets"))
-                    .await
-                    .expect("Failed to load assets"),
-            };
+            let assets = load_assets(&geng, assets).await;
             let level_name = level.to_string_lossy().to_string();
-            let level: Level =
-                geng::LoadAsset::load(&geng, &run_dir().join("assets").join("levels").join(level))
-                    .await
-                    .expect("Failed to load level");
+            let level = load_level(&geng, &level).await;
             Game::new(
-                &geng, &assets, level_name, level, coins, time, deaths, show_time, music,
+                &geng, &assets, pack, level_name, level, coins, time, deaths, show_time, assists,
+                juice, mixer, music,
             )
         }
     };
     geng::LoadingScreen::new(geng, geng::EmptyLoadingScreen, future, |state| state)
 }
+
+async fn load_assets(geng: &Geng, assets: Option<Rc<Assets>>) -> Rc<Assets> {
+    match assets {
+        Some(assets) => assets,
+        None => geng::LoadAsset::load(geng, &run_dir().join("assets"))
+            .await
+            .expect("Failed to load assets"),
+    }
+}
+
+async fn load_level(geng: &Geng, level: impl AsRef<std::path::Path>) -> Level {
+    Level::load_async(geng, level)
+        .await
+        .expect("Failed to load level")
+}
@@ -0,0 +1,84 @@
+//! Watching the assets directory for changes on native builds.
+
+use super::*;
+
+use notify::Watcher;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetChange {
+    /// `rules.json` has changed.
+    Rules,
+    /// A level file has changed.
+    Level(String),
+    /// A tileset image or its config has changed.
+    TileSet,
+    /// `particles.json` has changed.
+    Particles,
+    /// `post.json` or a color grade has changed.
+    Post,
+}
+
+pub struct AssetsWatcher {
+    /// Stops watching when dropped.
+    _watcher: notify::RecommendedWatcher,
+    receiver: std::sync::mpsc::Receiver<std::path::PathBuf>,
+}
+
+impl AssetsWatcher {
+    pub fn new() -> anyhow::Result<Self> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) => {
+                    if event.kind.is_modify() || event.kind.is_create() {
+                        for path in event.paths {
+                            // The receiver is gone only when the watcher is dropped
+                            let _ = sender.send(path);
+                        }
+                    }
+                }
+                Err(err) => error!("Failed to watch the assets: {err}"),
+            })?;
+        watcher.watch(&run_dir().join("assets"), notify::RecursiveMode::Recursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    /// Collects the changes since the last call without duplicates.
+    pub fn changes(&self) -> Vec<AssetChange> {
+        let mut changes = Vec::new();
+        for path in self.receiver.try_iter() {
+            if let Some(change) = AssetChange::from_path(&path) {
+                if !changes.contains(&change) {
+                    changes.push(change);
+                }
+            }
+        }
+        changes
+    }
+}
+
+impl AssetChange {
+    fn from_path(path: &std::path::Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        let extension = path.extension()?.to_str()?;
+        let parent = path.parent()?;
+        let parent_name = parent.file_name()?.to_str()?;
+
+        if file_name == "rules.json" && parent_name == "assets" {
+            Some(Self::Rules)
+        } else if parent_name == "levels" && extension == "json" {
+            Some(Self::Level(file_name.to_owned()))
+        } else if parent_name == "tiles" && (extension == "png" || extension == "json") {
+            Some(Self::TileSet)
+        } else if file_name == "particles.json" && parent_name == "assets" {
+            Some(Self::Particles)
+        } else if (file_name == "post.json" && parent_name == "assets") || parent_name == "grades" {
+            Some(Self::Post)
+        } else {
+            None
+        }
+    }
+}
@@ -46,15 +46,18 @@ impl geng::State for Intro {
             self.transition = Some(geng::Transition::Switch(Box::new(game::run(
                 &self.geng,
                 Some(&self.assets),
-                "intro_01.json",
+                Some(game::DEFAULT_PACK),
+                None,
             ))));
             return;
         }
 
-        let reference_size = vec2(16.0, 9.0);
-        let ratio = framebuffer.size().map(|x| x as f32) / reference_size;
-        let ratio = ratio.x.min(ratio.y);
-        let target_size = reference_size * ratio;
+        // The intro is drawn at the reference resolution
+        let target_size = self
+            .assets
+            .screen
+            .fit_fixed(SCREEN_RESOLUTION, framebuffer.size())
+            .size();
 
         self.zoom = self.time.max(Time::ONE);
         let zoom = (self.zoom.as_f32() - 1.0).min(1.0);
@@ -4,11 +4,21 @@ use geng::Camera2d;
 mod assets;
 mod editor;
 mod game;
+#[cfg(not(target_arch = "wasm32"))]
+mod hot_reload;
 mod intro;
 mod model;
 mod render;
+#[cfg(not(target_arch = "wasm32"))]
+mod thumbnail;
+#[cfg(not(target_arch = "wasm32"))]
+mod tile_check;
+#[cfg(not(target_arch = "wasm32"))]
+mod tiled;
 mod ui;
 mod util;
+#[cfg(target_arch = "wasm32")]
+mod web;
 
 use assets::*;
 use model::*;
@@ -17,6 +27,8 @@ use render::*;
 const FPS: f64 = 60.0;
 
 const PIXELS_PER_UNIT: f32 = 8.0;
+/// Reference resolution the art is made for.
+/// The actual resolution depends on the window, see [ScreenConfig].
 const SCREEN_RESOLUTION: vec2<usize> = vec2(320, 180);
 
 #[derive(clap::Parser)]
@@ -25,6 +37,9 @@ struct Opt {
     editor: bool,
     #[clap(long)]
     level: Option<String>,
+    /// Level pack to play, defaults to the main one.
+    #[clap(long)]
+    pack: Option<String>,
     #[clap(long)]
     #[cfg(not(target_arch = "wasm32"))]
     change_size: Option<String>,
@@ -43,6 +58,14 @@ enum Command {
     ChangeSize(ChangeSizeOpt),
     #[cfg(not(target_arch = "wasm32"))]
     Format,
+    #[cfg(not(target_arch = "wasm32"))]
+    TiledImport(TiledImportOpt),
+    #[cfg(not(target_arch = "wasm32"))]
+    TiledExport(TiledExportOpt),
+    #[cfg(not(target_arch = "wasm32"))]
+    Thumbnail(ThumbnailOpt),
+    #[cfg(not(target_arch = "wasm32"))]
+    TileCheck(TileCheckOpt),
 }
 
 #[derive(clap::Args)]
@@ -56,11 +79,52 @@ struct ChangeSizeOpt {
     size: String,
 }
 
+#[derive(clap::Args)]
+struct TiledImportOpt {
+    /// Path to the Tiled map in the json format.
+    map: String,
+}
+
+#[derive(clap::Args)]
+struct TiledExportOpt {
+    /// Path to save the Tiled map at.
+    map: String,
+}
+
+#[derive(clap::Args)]
+struct ThumbnailOpt {
+    /// Path to save the image at, or the directory for the images of a whole `--pack`.
+    output: String,
+    /// Pixels per world unit, defaults to the in-game scale.
+    #[clap(long)]
+    scale: Option<f32>,
+    /// Downscales the image to fit into a square of this size.
+    #[clap(long)]
+    max_size: Option<u32>,
+}
+
+#[derive(clap::Args)]
+struct TileCheckOpt {
+    /// Paths to the tileset images, defaults to all the game tilesets.
+    tilesets: Vec<String>,
+    /// Directory to save the sheets with every neighbourhood rendered in.
+    #[clap(long)]
+    sheet: Option<String>,
+}
+
 fn main() {
     logger::init().unwrap();
     geng::setup_panic_handler();
     let opt: Opt = program_args::parse();
 
+    // These commands only use the CPU, so they run without a window or a GPU
+    #[cfg(not(target_arch = "wasm32"))]
+    match &opt.command {
+        Some(Command::Thumbnail(config)) => return thumbnail_command(&opt, config),
+        Some(Command::TileCheck(config)) => return tile_check_command(config),
+        _ => {}
+    }
+
     let geng = Geng::new_with(geng::ContextOptions {
         title: "Drill above".to_string(),
         fixed_delta_time: 1.0 / FPS,
@@ -86,7 +150,9 @@ fn main() {
                                 image::DynamicImage::ImageRgba8(image) => image,
                                 _ => image.to_rgba8(),
                             };
-                            let config = TileSetConfig::generate_from(&texture, size);
+                            let config = TileSetConfig::generate_from(&texture, size)
+                                .expect("Failed to generate the tileset config");
+                            tile_check::report("tileset", &config);
                             let file = std::fs::File::create(run_dir().join("tileset_config.json"))
                                 .unwrap();
                             let writer = std::io::BufWriter::new(file);
@@ -124,19 +190,146 @@ fn main() {
                 level.save(level_path).expect("Failed to save the level");
                 info!("Saved the changed level at {}", level_path);
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            Command::TiledImport(config) => {
+                let path = run_dir().join(config.map);
+                let file = std::fs::File::open(&path)
+                    .unwrap_or_else(|_| panic!("Failed to open {path:?}"));
+                let reader = std::io::BufReader::new(file);
+                let map: tiled::TiledMap =
+                    serde_json::from_reader(reader).expect("Failed to parse the Tiled map");
+                let level = map.to_level().expect("Failed to convert the Tiled map");
+
+                let level_path = opt.level.as_deref().unwrap_or("new_level.json");
+                level.save(level_path).expect("Failed to save the level");
+                info!("Saved the imported level at {}", level_path);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Command::TiledExport(config) => {
+                let level_path = opt
+                    .level
+                    .as_ref()
+                    .expect("tiled export requires a --level argument");
+                let level = Level::load(level_path).expect("Failed to load the level");
+                let tiles_dir = run_dir().join("assets").join("sprites").join("tiles");
+                let path = run_dir().join(config.map);
+                let map_dir = path.parent().unwrap_or(&path);
+                let map = tiled::TiledMap::from_level(&level, &tiles_dir, map_dir);
+
+                let file = std::fs::File::create(&path)
+                    .unwrap_or_else(|_| panic!("Failed to create {path:?}"));
+                let writer = std::io::BufWriter::new(file);
+                serde_json::to_writer_pretty(writer, &map).expect("Failed to save the Tiled map");
+                info!("Saved the Tiled map at {:?}", path);
+            }
+            // Handled before creating the window
+            #[cfg(not(target_arch = "wasm32"))]
+            Command::Thumbnail(_) | Command::TileCheck(_) => unreachable!(),
         }
         return;
     }
 
     if opt.editor {
         geng::run(&geng, editor::run(&geng, opt.level))
-    } else if let Some(level) = &opt.level {
-        geng::run(&geng, game::run(&geng, None, level))
+    } else if opt.level.is_some() || opt.pack.is_some() {
+        geng::run(
+            &geng,
+            game::run(&geng, None, opt.pack.as_deref(), opt.level),
+        )
     } else {
         geng::run(&geng, intro::run(&geng))
     }
 }
 
+/// Renders the thumbnails of the `--level` or of the whole `--pack`.
+#[cfg(not(target_arch = "wasm32"))]
+fn thumbnail_command(opt: &Opt, config: &ThumbnailOpt) {
+    let output = run_dir().join(&config.output);
+    let levels: Vec<(String, std::path::PathBuf)> = match (&opt.level, &opt.pack) {
+        (Some(level), _) => vec![(level.clone(), output)],
+        (None, Some(pack)) => {
+            let path = run_dir().join("assets").join("packs").join(pack);
+            let file =
+                std::fs::File::open(&path).unwrap_or_else(|_| panic!("Failed to open {path:?}"));
+            let reader = std::io::BufReader::new(file);
+            let pack: LevelPack =
+                serde_json::from_reader(reader).expect("Failed to parse the level pack");
+            std::fs::create_dir_all(&output)
+                .unwrap_or_else(|_| panic!("Failed to create {output:?}"));
+            pack.levels()
+                .chain(&pack.credits)
+                .map(|name| {
+                    let image = std::path::Path::new(name).with_extension("png");
+                    (name.clone(), output.join(image))
+                })
+                .collect()
+        }
+        (None, None) => panic!("thumbnail requires a --level or a --pack argument"),
+    };
+
+    let assets_dir = run_dir().join("assets");
+    let render = thumbnail::ThumbnailRender::load(&assets_dir).expect("Failed to load the sprites");
+    let scale = config.scale.unwrap_or(PIXELS_PER_UNIT);
+    for (level_path, image_path) in levels {
+        let level = Level::load(&level_path).expect("Failed to load the level");
+        let mut image = render.render(&level, scale);
+        if let Some(max_size) = config.max_size {
+            image = thumbnail::fit_image(image, max_size);
+        }
+        image
+            .save(&image_path)
+            .unwrap_or_else(|_| panic!("Failed to save {image_path:?}"));
+        info!("Saved the thumbnail of {} at {:?}", level_path, image_path);
+    }
+}
+
+/// Reports the coverage of the tilesets, exits with an error if any is incomplete.
+#[cfg(not(target_arch = "wasm32"))]
+fn tile_check_command(config: &TileCheckOpt) {
+    let tilesets: Vec<std::path::PathBuf> = if config.tilesets.is_empty() {
+        let tiles_dir = run_dir().join("assets").join("sprites").join("tiles");
+        ["mask", "air", "grass", "stone"]
+            .into_iter()
+            .map(|name| tiles_dir.join(format!("{name}.png")))
+            .collect()
+    } else {
+        config
+            .tilesets
+            .iter()
+            .map(|path| run_dir().join(path))
+            .collect()
+    };
+
+    let mut covered = true;
+    for path in tilesets {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let config_path = path.with_file_name(format!("{name}_config.json"));
+        let file = std::fs::File::open(&config_path)
+            .unwrap_or_else(|_| panic!("Failed to open {config_path:?}"));
+        let reader = std::io::BufReader::new(file);
+        let tileset: TileSetConfig =
+            serde_json::from_reader(reader).expect("Failed to parse the tileset config");
+        covered &= tile_check::report(&name, &tileset);
+
+        if let Some(dir) = &config.sheet {
+            let texture = image::open(&path)
+                .unwrap_or_else(|_| panic!("Failed to load {path:?}"))
+                .into_rgba8();
+            let sheet = tile_check::render_sheet(&texture, &tileset);
+            let dir = run_dir().join(dir);
+            std::fs::create_dir_all(&dir).unwrap_or_else(|_| panic!("Failed to create {dir:?}"));
+            let sheet_path = dir.join(format!("{name}_coverage.png"));
+            sheet
+                .save(&sheet_path)
+                .unwrap_or_else(|_| panic!("Failed to save {sheet_path:?}"));
+            info!("Saved the coverage sheet at {:?}", sheet_path);
+        }
+    }
+    if !covered {
+        std::process::exit(1);
+    }
+}
+
 fn parse_size(input: &str) -> Option<vec2<usize>> {
     let mut xs = input.split('x');
     let pos = vec2(xs.next()?.parse().ok()?, xs.next()?.parse().ok()?);
@@ -0,0 +1,33 @@
+use super::*;
+
+/// Accessibility options that make the game easier.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Assists {
+    /// Multiplier of the game speed.
+    pub game_speed: f32,
+    /// Hazards do not kill the player.
+    pub invincible: bool,
+    /// Drill dash is always available.
+    pub infinite_drill_dash: bool,
+    /// Whether any assist has been used during the run.
+    pub used: bool,
+}
+
+impl Assists {
+    pub const MIN_GAME_SPEED: f32 = 0.25;
+
+    pub fn is_active(&self) -> bool {
+        self.game_speed < 1.0 || self.invincible || self.infinite_drill_dash
+    }
+}
+
+impl Default for Assists {
+    fn default() -> Self {
+        Self {
+            game_speed: 1.0,
+            invincible: false,
+            infinite_drill_dash: false,
+            used: false,
+        }
+    }
+}
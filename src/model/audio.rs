@@ -0,0 +1,93 @@
+use super::*;
+
+/// Volume of the audio buses, each in range `0..=1`.
+#[derive(Debug, Clone, Copy)]
+pub struct Mixer {
+    pub master: f64,
+    pub music: f64,
+    pub sfx: f64,
+}
+
+/// Sound effects played by the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sfx {
+    Jump,
+    Death,
+    Coin,
+    DrillJump,
+    Charm,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct AudioConfig {
+    /// How much the sounds at the edge of the screen are panned, from 0 to 1.
+    pub pan: f64,
+    /// Maximum random change of the pitch of the repeated sounds.
+    #[serde(default)]
+    pub pitch_variation: HashMap<Sfx, f64>,
+    pub drill: DrillSoundConfig,
+}
+
+/// How the drill loop reacts to the player's speed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrillSoundConfig {
+    pub min_speed: Coord,
+    pub max_speed: Coord,
+    pub min_pitch: f64,
+    pub max_pitch: f64,
+    pub min_volume: f64,
+    pub max_volume: f64,
+}
+
+impl Mixer {
+    pub fn music_volume(&self) -> f64 {
+        self.master * self.music
+    }
+
+    pub fn sfx_volume(&self) -> f64 {
+        self.master * self.sfx
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            master: 0.5,
+            music: 0.4,
+            sfx: 1.0,
+        }
+    }
+}
+
+impl DrillSoundConfig {
+    /// Returns the pitch and the volume of the drill loop at the speed.
+    pub fn get(&self, speed: Coord) -> (f64, f64) {
+        let range = self.max_speed - self.min_speed;
+        let t = if range > Coord::ZERO {
+            ((speed - self.min_speed) / range)
+                .clamp(Coord::ZERO, Coord::ONE)
+                .as_f32() as f64
+        } else {
+            // No range to interpolate in, the drill is always at full speed
+            1.0
+        };
+        (
+            self.min_pitch + (self.max_pitch - self.min_pitch) * t,
+            self.min_volume + (self.max_volume - self.min_volume) * t,
+        )
+    }
+}
+
+impl Sounds {
+    pub fn get_sfx(&self, sfx: Sfx) -> &geng::Sound {
+        match sfx {
+            Sfx::Jump => &self.jump,
+            Sfx::Death => &self.death,
+            Sfx::Coin => &self.coin,
+            Sfx::DrillJump => &self.drill_jump,
+            Sfx::Charm => &self.charm,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use super::*;
+
+/// Field of view of the camera at the reference resolution.
+pub const DEFAULT_FOV: f32 = 22.5;
+
+/// Tuning of the camera movement.
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct CameraConfig {
+    /// How fast the camera catches up with the target (per second).
+    pub smoothing: Coord,
+    /// How far ahead the camera looks, in seconds of the player's velocity.
+    pub look_ahead_time: Time,
+    /// Maximum distance of the look-ahead.
+    pub look_ahead_max: Coord,
+    /// How fast the look-ahead changes (per second).
+    pub look_ahead_smoothing: Coord,
+    /// Half the height of the vertical dead zone while grounded.
+    pub dead_zone: Coord,
+    /// How fast the zoom changes (per second).
+    pub zoom_smoothing: f32,
+}
+
+/// Smoothed camera position, not snapped to pixels.
+#[derive(Debug, Clone)]
+pub struct CameraState {
+    pub position: vec2<Coord>,
+    pub look_ahead: vec2<Coord>,
+    /// Vertical position the camera follows while grounded.
+    pub anchor_y: Coord,
+}
+
+/// A level region that changes the camera behaviour while the player is inside.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraZone {
+    pub area: Aabb2<Coord>,
+    pub mode: CameraZoneMode,
+    /// Field of view override at the reference resolution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fov: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CameraZoneMode {
+    /// The camera stays in the center of the zone.
+    Lock,
+    /// The camera view stays inside the zone.
+    Limit,
+}
+
+impl CameraState {
+    pub fn new(position: vec2<Coord>) -> Self {
+        Self {
+            position,
+            look_ahead: vec2::ZERO,
+            anchor_y: position.y,
+        }
+    }
+}
+
+impl CameraZone {
+    pub fn new(area: Aabb2<Coord>, mode: CameraZoneMode) -> Self {
+        Self {
+            area,
+            mode,
+            fov: None,
+        }
+    }
+
+    pub fn teleport(&mut self, pos: vec2<Coord>) {
+        self.area = self.area.translate(pos - self.area.center());
+    }
+
+    pub fn translate(&mut self, delta: vec2<Coord>) {
+        self.area = self.area.translate(delta);
+    }
+}
+
+impl CameraZoneMode {
+    pub fn all() -> [Self; 2] {
+        use CameraZoneMode::*;
+        [Limit, Lock]
+    }
+}
@@ -0,0 +1,142 @@
+use super::*;
+
+/// Player preferences for the screen effects.
+#[derive(Debug, Clone, Copy)]
+pub struct JuiceSettings {
+    /// Disables all of the effects, for motion-sensitive players.
+    pub enabled: bool,
+    /// Multiplier of the effects' strength.
+    pub intensity: f32,
+}
+
+/// Strength of the effects for each gameplay event.
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct JuiceConfig {
+    /// Maximum camera offset at full shake.
+    pub max_shake: Coord,
+    /// How fast the shake wears off (per second).
+    pub shake_decay: f32,
+    /// How fast the player sprite returns to normal (per second).
+    pub squash_decay: f32,
+    /// How fast the flash fades (per second).
+    pub flash_decay: f32,
+    pub death: JuiceEffect,
+    pub drill_enter: JuiceEffect,
+    pub drill_exit: JuiceEffect,
+    pub drill_jump: JuiceEffect,
+    pub finish: JuiceEffect,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JuiceEffect {
+    /// Camera shake added, from 0 to 1.
+    #[serde(default)]
+    pub shake: f32,
+    /// Duration of the freeze.
+    #[serde(default)]
+    pub hit_stop: Time,
+    /// Positive values stretch the player vertically, negative squash.
+    #[serde(default)]
+    pub squash: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flash: Option<Rgba<f32>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum JuiceEvent {
+    Death,
+    DrillEnter,
+    DrillExit,
+    DrillJump,
+    Finish,
+}
+
+/// Current state of the screen effects.
+#[derive(Debug, Clone)]
+pub struct Juice {
+    pub settings: JuiceSettings,
+    pub shake: f32,
+    /// Camera offset from the shake this frame.
+    pub shake_offset: vec2<Coord>,
+    /// Time left until the world unfreezes.
+    pub hit_stop: Time,
+    pub squash: f32,
+    pub flash: Rgba<f32>,
+}
+
+impl Default for JuiceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl JuiceConfig {
+    pub fn get_effect(&self, event: JuiceEvent) -> &JuiceEffect {
+        match event {
+            JuiceEvent::Death => &self.death,
+            JuiceEvent::DrillEnter => &self.drill_enter,
+            JuiceEvent::DrillExit => &self.drill_exit,
+            JuiceEvent::DrillJump => &self.drill_jump,
+            JuiceEvent::Finish => &self.finish,
+        }
+    }
+}
+
+impl Juice {
+    pub fn new(settings: JuiceSettings) -> Self {
+        Self {
+            settings,
+            shake: 0.0,
+            shake_offset: vec2::ZERO,
+            hit_stop: Time::ZERO,
+            squash: 0.0,
+            flash: Rgba::TRANSPARENT_BLACK,
+        }
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.hit_stop > Time::ZERO
+    }
+
+    /// Triggers the effects of the event scaled by the settings.
+    pub fn trigger(&mut self, config: &JuiceConfig, event: JuiceEvent) {
+        if !self.settings.enabled {
+            return;
+        }
+        let intensity = self.settings.intensity;
+        let effect = config.get_effect(event);
+        self.shake = (self.shake + effect.shake * intensity).min(1.0);
+        self.hit_stop = self
+            .hit_stop
+            .max(effect.hit_stop * Time::new(intensity.min(1.0)));
+        if effect.squash != 0.0 {
+            self.squash = effect.squash * intensity;
+        }
+        if let Some(mut flash) = effect.flash {
+            flash.a = (flash.a * intensity).min(1.0);
+            self.flash = flash;
+        }
+    }
+
+    pub fn update(&mut self, config: &JuiceConfig, delta_time: Time) {
+        let delta_time = delta_time.as_f32();
+        self.hit_stop = (self.hit_stop - Time::new(delta_time)).max(Time::ZERO);
+        self.shake = (self.shake - config.shake_decay * delta_time).max(0.0);
+        self.squash *= (1.0 - config.squash_decay * delta_time).max(0.0);
+        self.flash.a = (self.flash.a - config.flash_decay * delta_time).max(0.0);
+
+        // Squared for a smoother falloff
+        let strength = self.shake * self.shake;
+        self.shake_offset = if strength > 0.0 {
+            let mut rng = thread_rng();
+            vec2(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0))
+                .map(|x: f32| config.max_shake * Coord::new(x * strength))
+        } else {
+            vec2::ZERO
+        };
+    }
+}
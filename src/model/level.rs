@@ -4,6 +4,9 @@ use super::*;
 #[asset(json)]
 pub struct Level {
     pub drill_allowed: bool,
+    /// Overrides of the global rules for this level.
+    #[serde(default, skip_serializing_if = "RulesPatch::is_empty")]
+    pub rules: RulesPatch,
     #[serde(default)]
     pub grid: Grid,
     pub size: vec2<usize>,
@@ -20,6 +23,18 @@ pub struct Level {
     pub global_light: GlobalLightSource,
     #[serde(default)]
     pub spotlights: Vec<SpotlightSource>,
+    #[serde(default)]
+    pub point_lights: Vec<PointLightSource>,
+    /// Lights following the player, the coins or the hazards.
+    #[serde(default)]
+    pub attached_lights: Vec<AttachedLight>,
+    #[serde(default)]
+    pub camera_zones: Vec<CameraZone>,
+    /// Color grade of the level, see [ColorGrades].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    /// Overrides the next level from the level pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub next_level: Option<String>,
 }
 
@@ -29,16 +44,20 @@ pub enum BlockType {
     Hazard(HazardType),
     Prop(PropType),
     Spotlight(SpotlightSource),
+    PointLight(PointLightSource),
+    CameraZone(CameraZoneMode),
     Coin,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlockId {
     Tile(vec2<isize>),
     Hazard(usize),
     Prop(usize),
     Coin(usize),
     Spotlight(usize),
+    PointLight(usize),
+    CameraZone(usize),
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +67,8 @@ pub enum Block {
     Prop(Prop),
     Coin(Coin),
     Spotlight(SpotlightSource),
+    PointLight(PointLightSource),
+    CameraZone(CameraZone),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,12 +91,12 @@ pub struct Prop {
     pub prop_type: PropType,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HazardType {
     Spikes,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PropType {
     DrillUse,
     DrillJump,
@@ -108,8 +129,13 @@ impl Level {
             props: Vec::new(),
             next_level: None,
             drill_allowed: true,
+            rules: default(),
             global_light: default(),
             spotlights: Vec::new(),
+            point_lights: Vec::new(),
+            attached_lights: Vec::new(),
+            camera_zones: Vec::new(),
+            grade: None,
             grid,
             size,
         }
@@ -133,14 +159,30 @@ impl Level {
                 .map(|tile| !matches!(tile, Tile::Air))
                 .unwrap_or(false)
         };
-        let (direction, collider) = match hazard {
+        let direction = match hazard {
+            HazardType::Spikes => -[vec2(1, 0), vec2(-1, 0), vec2(0, 1)]
+                .into_iter()
+                .find(|&d| connect(pos + d))
+                .unwrap_or(vec2(0, -1))
+                .map(|x| Coord::new(x as f32)),
+        };
+        let pos = self.grid.grid_to_world(pos);
+        let mut hazard = Hazard {
+            sprite: Aabb2::point(pos).extend_positive(self.grid.cell_size),
+            collider: Collider::new(Aabb2::point(pos)),
+            direction: None,
+            hazard_type: hazard,
+        };
+        self.orient_hazard(&mut hazard, direction);
+        self.hazards.push(hazard);
+    }
+
+    /// Turns the hazard in its cell to face the direction and updates its collider.
+    pub fn orient_hazard(&self, hazard: &mut Hazard, direction: vec2<Coord>) {
+        let collider = match hazard.hazard_type {
             HazardType::Spikes => {
                 let size = vec2(0.8, 0.4);
-                let direction = -[vec2(1, 0), vec2(-1, 0), vec2(0, 1)]
-                    .into_iter()
-                    .find(|&d| connect(pos + d))
-                    .unwrap_or(vec2(0, -1))
-                    .map(|x| x as f32);
+                let direction = direction.map(Coord::as_f32);
                 let pos = vec2(0.5, 0.5) - direction * 0.5;
                 let aabb = Aabb2::from_corners(
                     pos + vec2(-size.x * direction.y * 0.5, -size.x * direction.x * 0.5),
@@ -150,21 +192,12 @@ impl Level {
                     ),
                 );
                 let aabb = aabb.map(Coord::new);
-                (
-                    Some(direction.map(Coord::new)),
-                    Aabb2::point(aabb.bottom_left() * self.grid.cell_size)
-                        .extend_positive(aabb.size() * self.grid.cell_size),
-                )
+                Aabb2::point(aabb.bottom_left() * self.grid.cell_size)
+                    .extend_positive(aabb.size() * self.grid.cell_size)
             }
         };
-        let pos = self.grid.grid_to_world(pos);
-        let collider = Collider::new(collider.translate(pos));
-        self.hazards.push(Hazard {
-            sprite: Aabb2::point(pos).extend_positive(self.grid.cell_size),
-            collider,
-            direction,
-            hazard_type: hazard,
-        });
+        hazard.direction = Some(direction);
+        hazard.collider = Collider::new(collider.translate(hazard.sprite.bottom_left()));
     }
 
     pub fn place_prop(&mut self, pos: vec2<isize>, size: vec2<Coord>, prop: PropType) {
@@ -186,6 +219,55 @@ impl Level {
         });
     }
 
+    /// Places a camera zone the size of the default camera view.
+    pub fn place_camera_zone(&mut self, pos: vec2<Coord>, mode: CameraZoneMode) {
+        let view = SCREEN_RESOLUTION.map(|x| Coord::new(x as f32 / PIXELS_PER_UNIT));
+        let area = Aabb2::point(pos).extend_symmetric(view / Coord::new(2.0));
+        self.camera_zones.push(CameraZone::new(area, mode));
+    }
+
+    /// Collects the lights of the level at the given time.
+    /// The player is described by its position and the angle it is facing.
+    pub fn scene_lights(&self, time: f32, player: (vec2<Coord>, f32)) -> SceneLights {
+        let mut lights = SceneLights::new(self.global_light);
+        for light in &self.spotlights {
+            lights.push(LightSource::Spot(*light), time, light_seed(light.position));
+        }
+        for light in &self.point_lights {
+            lights.push(LightSource::Point(*light), time, light_seed(light.position));
+        }
+        for (i, attached) in self.attached_lights.iter().enumerate() {
+            match attached.target {
+                LightTarget::Player => {
+                    let (pos, angle) = player;
+                    let mut offset = attached.offset;
+                    if angle.cos() < 0.0 {
+                        offset.x = -offset.x;
+                    }
+                    // The player moves, so the seed comes from the light itself
+                    let seed = light_seed(attached.offset) + i as f32;
+                    lights.push(attached.light.at(pos + offset, angle), time, seed);
+                }
+                LightTarget::Coins => {
+                    for coin in &self.coins {
+                        let pos = coin.collider.pos() + attached.offset;
+                        lights.push(attached.light.at(pos, 0.0), time, light_seed(pos));
+                    }
+                }
+                LightTarget::Hazards => {
+                    for hazard in &self.hazards {
+                        let angle = hazard
+                            .direction
+                            .map_or(0.0, |dir| dir.map(Coord::as_f32).arg() - f32::PI / 2.0);
+                        let pos = hazard.collider.pos() + attached.offset;
+                        lights.push(attached.light.at(pos, angle), time, light_seed(pos));
+                    }
+                }
+            }
+        }
+        lights
+    }
+
     pub fn get_hovered(&mut self, pos: vec2<Coord>) -> Vec<BlockId> {
         let grid_pos = self.grid.world_to_grid(pos).0;
         itertools::chain![
@@ -194,6 +276,11 @@ impl Level {
                 .enumerate()
                 .filter(|(_, spotlight)| (spotlight.position - pos).len() < Coord::new(0.5))
                 .map(|(i, _)| BlockId::Spotlight(i)),
+            self.point_lights
+                .iter()
+                .enumerate()
+                .filter(|(_, light)| (light.position - pos).len() < Coord::new(0.5))
+                .map(|(i, _)| BlockId::PointLight(i)),
             self.props
                 .iter()
                 .enumerate()
@@ -209,6 +296,11 @@ impl Level {
                 .enumerate()
                 .filter(|(_, hazard)| hazard.collider.contains(pos))
                 .map(|(i, _)| BlockId::Coin(i)),
+            self.camera_zones
+                .iter()
+                .enumerate()
+                .filter(|(_, zone)| zone.area.contains(pos))
+                .map(|(i, _)| BlockId::CameraZone(i)),
             self.tiles
                 .get_tile_isize(grid_pos)
                 .map(|_| BlockId::Tile(grid_pos)),
@@ -226,14 +318,160 @@ impl Level {
             BlockId::Prop(id) => self.props.get(id).cloned().map(Block::Prop),
             BlockId::Coin(id) => self.coins.get(id).cloned().map(Block::Coin),
             BlockId::Spotlight(id) => self.spotlights.get(id).cloned().map(Block::Spotlight),
+            BlockId::PointLight(id) => self.point_lights.get(id).cloned().map(Block::PointLight),
+            BlockId::CameraZone(id) => self.camera_zones.get(id).cloned().map(Block::CameraZone),
+        }
+    }
+
+    /// Replaces the block with the given id by a block of the same type.
+    /// Returns the replaced block.
+    pub fn set_block(&mut self, id: BlockId, block: Block) -> Option<Block> {
+        let old = self.get_block(id)?;
+        match (id, block) {
+            (BlockId::Tile(pos), Block::Tile((tile, _))) => self.tiles.set_tile_isize(pos, tile),
+            (BlockId::Hazard(id), Block::Hazard(hazard)) => self.hazards[id] = hazard,
+            (BlockId::Prop(id), Block::Prop(prop)) => self.props[id] = prop,
+            (BlockId::Coin(id), Block::Coin(coin)) => self.coins[id] = coin,
+            (BlockId::Spotlight(id), Block::Spotlight(light)) => self.spotlights[id] = light,
+            (BlockId::PointLight(id), Block::PointLight(light)) => self.point_lights[id] = light,
+            (BlockId::CameraZone(id), Block::CameraZone(zone)) => self.camera_zones[id] = zone,
+            _ => return None,
+        }
+        Some(old)
+    }
+
+    /// Returns the area taken by the block.
+    pub fn block_bounds(&self, id: BlockId) -> Option<Aabb2<Coord>> {
+        let light_area = |pos: vec2<Coord>| Aabb2::point(pos).extend_uniform(Coord::new(0.5));
+        match id {
+            BlockId::Tile(pos) => self.tiles.get_tile_isize(pos).map(|_| {
+                Aabb2::point(self.grid.grid_to_world(pos)).extend_positive(self.grid.cell_size)
+            }),
+            BlockId::Hazard(id) => self.hazards.get(id).map(|hazard| hazard.sprite),
+            BlockId::Prop(id) => self.props.get(id).map(|prop| prop.sprite),
+            BlockId::Coin(id) => self.coins.get(id).map(|coin| coin.collider.raw()),
+            BlockId::Spotlight(id) => self.spotlights.get(id).map(|l| light_area(l.position)),
+            BlockId::PointLight(id) => self.point_lights.get(id).map(|l| light_area(l.position)),
+            BlockId::CameraZone(id) => self.camera_zones.get(id).map(|zone| zone.area),
+        }
+    }
+
+    /// Returns the grid cell the block is placed at.
+    pub fn block_cell(&self, block: &Block) -> vec2<isize> {
+        match block {
+            Block::Tile((_, pos)) => *pos,
+            _ => self.grid.world_to_grid(block.position()).0,
         }
     }
 
-    pub fn remove_blocks(&mut self, blocks: &[BlockId]) -> Vec<Block> {
+    /// Returns the non-air tiles with the centers inside the area,
+    /// and the other blocks positioned inside it.
+    pub fn blocks_in(&self, area: Aabb2<Coord>) -> Vec<BlockId> {
+        let half_cell = self.grid.cell_size / Coord::new(2.0);
+        let tiles = self
+            .tiles
+            .tiles()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| !matches!(tile, Tile::Air))
+            .map(|(i, _)| index_to_pos(i, self.size.x).map(|x| x as isize))
+            .filter(|&pos| area.contains(self.grid.grid_to_world(pos) + half_cell))
+            .map(BlockId::Tile);
+        let others = itertools::chain![
+            (0..self.spotlights.len()).map(BlockId::Spotlight),
+            (0..self.point_lights.len()).map(BlockId::PointLight),
+            (0..self.props.len()).map(BlockId::Prop),
+            (0..self.hazards.len()).map(BlockId::Hazard),
+            (0..self.coins.len()).map(BlockId::Coin),
+            (0..self.camera_zones.len()).map(BlockId::CameraZone),
+        ]
+        .filter(|&id| match self.get_block(id) {
+            Some(block) => area.contains(block.position()),
+            None => false,
+        });
+        tiles.chain(others).collect()
+    }
+
+    /// Inserts the block into the level, tiles replace the existing ones.
+    pub fn insert_block(&mut self, block: Block) -> BlockId {
+        match block {
+            Block::Tile((tile, pos)) => {
+                self.tiles.set_tile_isize(pos, tile);
+                BlockId::Tile(pos)
+            }
+            Block::Hazard(hazard) => {
+                self.hazards.push(hazard);
+                BlockId::Hazard(self.hazards.len() - 1)
+            }
+            Block::Prop(prop) => {
+                self.props.push(prop);
+                BlockId::Prop(self.props.len() - 1)
+            }
+            Block::Coin(coin) => {
+                self.coins.push(coin);
+                BlockId::Coin(self.coins.len() - 1)
+            }
+            Block::Spotlight(light) => {
+                self.spotlights.push(light);
+                BlockId::Spotlight(self.spotlights.len() - 1)
+            }
+            Block::PointLight(light) => {
+                self.point_lights.push(light);
+                BlockId::PointLight(self.point_lights.len() - 1)
+            }
+            Block::CameraZone(zone) => {
+                self.camera_zones.push(zone);
+                BlockId::CameraZone(self.camera_zones.len() - 1)
+            }
+        }
+    }
+
+    /// Inserts the block at the position given by the id,
+    /// shifting the following blocks of the same type.
+    pub fn insert_block_at(&mut self, id: BlockId, block: Block) -> Option<BlockId> {
+        fn insert<T>(vec: &mut Vec<T>, index: usize, item: T) -> usize {
+            let index = index.min(vec.len());
+            vec.insert(index, item);
+            index
+        }
+        let id = match (id, block) {
+            (BlockId::Tile(pos), Block::Tile((tile, _))) => {
+                self.tiles.set_tile_isize(pos, tile);
+                BlockId::Tile(pos)
+            }
+            (BlockId::Hazard(i), Block::Hazard(hazard)) => {
+                BlockId::Hazard(insert(&mut self.hazards, i, hazard))
+            }
+            (BlockId::Prop(i), Block::Prop(prop)) => {
+                BlockId::Prop(insert(&mut self.props, i, prop))
+            }
+            (BlockId::Coin(i), Block::Coin(coin)) => {
+                BlockId::Coin(insert(&mut self.coins, i, coin))
+            }
+            (BlockId::Spotlight(i), Block::Spotlight(light)) => {
+                BlockId::Spotlight(insert(&mut self.spotlights, i, light))
+            }
+            (BlockId::PointLight(i), Block::PointLight(light)) => {
+                BlockId::PointLight(insert(&mut self.point_lights, i, light))
+            }
+            (BlockId::CameraZone(i), Block::CameraZone(zone)) => {
+                BlockId::CameraZone(insert(&mut self.camera_zones, i, zone))
+            }
+            _ => return None,
+        };
+        Some(id)
+    }
+
+    /// Removes the blocks, keeping the order of the remaining ones.
+    /// Returns the removed blocks with their ids, from the last one of each type,
+    /// so that inserting them back in the reverse order restores the level.
+    pub fn remove_blocks(&mut self, blocks: &[BlockId]) -> Vec<(BlockId, Block)> {
         let mut spotlights = Vec::new();
+        let mut point_lights = Vec::new();
         let mut props = Vec::new();
         let mut hazards = Vec::new();
         let mut coins = Vec::new();
+        let mut camera_zones = Vec::new();
         let mut tiles = Vec::new();
         for &block in blocks {
             match block {
@@ -242,34 +480,52 @@ impl Level {
                 BlockId::Prop(id) => props.push(id),
                 BlockId::Coin(id) => coins.push(id),
                 BlockId::Spotlight(id) => spotlights.push(id),
+                BlockId::PointLight(id) => point_lights.push(id),
+                BlockId::CameraZone(id) => camera_zones.push(id),
             }
         }
 
         spotlights.sort_unstable();
+        spotlights.dedup();
+        point_lights.sort_unstable();
+        point_lights.dedup();
         props.sort_unstable();
+        props.dedup();
         hazards.sort_unstable();
+        hazards.dedup();
         coins.sort_unstable();
+        coins.dedup();
+        camera_zones.sort_unstable();
+        camera_zones.dedup();
 
         let mut removed = Vec::new();
         for id in spotlights.into_iter().rev() {
-            let light = self.spotlights.swap_remove(id);
-            removed.push(Block::Spotlight(light));
+            let light = self.spotlights.remove(id);
+            removed.push((BlockId::Spotlight(id), Block::Spotlight(light)));
+        }
+        for id in point_lights.into_iter().rev() {
+            let light = self.point_lights.remove(id);
+            removed.push((BlockId::PointLight(id), Block::PointLight(light)));
         }
         for id in props.into_iter().rev() {
-            let prop = self.props.swap_remove(id);
-            removed.push(Block::Prop(prop));
+            let prop = self.props.remove(id);
+            removed.push((BlockId::Prop(id), Block::Prop(prop)));
         }
         for id in hazards.into_iter().rev() {
-            let hazard = self.hazards.swap_remove(id);
-            removed.push(Block::Hazard(hazard));
+            let hazard = self.hazards.remove(id);
+            removed.push((BlockId::Hazard(id), Block::Hazard(hazard)));
         }
         for id in coins.into_iter().rev() {
-            let coin = self.coins.swap_remove(id);
-            removed.push(Block::Coin(coin));
+            let coin = self.coins.remove(id);
+            removed.push((BlockId::Coin(id), Block::Coin(coin)));
+        }
+        for id in camera_zones.into_iter().rev() {
+            let zone = self.camera_zones.remove(id);
+            removed.push((BlockId::CameraZone(id), Block::CameraZone(zone)));
         }
         for pos in tiles {
             if let Some(tile) = self.tiles.get_tile_isize(pos) {
-                removed.push(Block::Tile((tile, pos)));
+                removed.push((BlockId::Tile(pos), Block::Tile((tile, pos))));
             }
             self.tiles.set_tile_isize(pos, Tile::Air);
         }
@@ -300,6 +556,12 @@ impl Level {
         for light in &mut self.spotlights {
             light.position += delta;
         }
+        for light in &mut self.point_lights {
+            light.position += delta;
+        }
+        for zone in &mut self.camera_zones {
+            zone.translate(delta);
+        }
     }
 
     pub fn calculate_geometry(
@@ -307,19 +569,24 @@ impl Level {
         geng: &Geng,
         assets: &Assets,
     ) -> (
-        HashMap<Tile, ugli::VertexBuffer<Vertex>>,
-        HashMap<Tile, ugli::VertexBuffer<MaskedVertex>>,
+        HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
     ) {
-        let mut tiles_geometry = HashMap::<Tile, Vec<Vertex>>::new();
-        let mut masked_geometry = HashMap::<Tile, Vec<MaskedVertex>>::new();
+        let mut tiles_geometry = HashMap::<TileGroup, Vec<Vertex>>::new();
+        let mut masked_geometry = HashMap::<TileGroup, Vec<MaskedVertex>>::new();
         let calc_geometry = |i: usize, tile: &Tile, connections: [Connection; 8]| {
             let pos = index_to_pos(i, self.size.x);
+            let seed = position_hash(pos);
             let pos = self.grid.grid_to_world(pos.map(|x| x as isize));
             let pos = Aabb2::point(pos)
                 .extend_positive(self.grid.cell_size)
                 .map(Coord::as_f32);
             let set = assets.sprites.tiles.get_tile_set(tile);
-            let geometry = set.get_tile_connected(connections);
+            let geometry = set.get_tile_variant(connections, seed);
+            let group = TileGroup {
+                tile: *tile,
+                animation: set.config.find_animation(geometry),
+            };
             let vertices = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
             let vertices = [0, 1, 2, 3].map(|i| Vertex {
                 a_pos: vec2(vertices[i].0, vertices[i].1),
@@ -334,13 +601,14 @@ impl Level {
                 vertices[3],
             ];
             let matrix = mat3::translate(pos.bottom_left()) * mat3::scale(pos.size());
-            geometry.map(|vertex| {
+            let geometry = geometry.map(|vertex| {
                 let pos = matrix * vertex.a_pos.extend(1.0);
                 Vertex {
                     a_pos: pos.xy() / pos.z,
                     ..vertex
                 }
-            })
+            });
+            (group, geometry)
         };
         for (i, tile) in self.tiles.tiles().iter().enumerate() {
             if let Tile::Air = tile {
@@ -350,28 +618,28 @@ impl Level {
             let connections = self.tiles.get_tile_connections(i);
             let neighbours = self.tiles.get_tile_neighbours(i);
             if neighbours.contains(&Some(Tile::Grass)) {
-                let geometry = calc_geometry(i, &Tile::Grass, connections);
-                let mask = assets.sprites.tiles.mask.get_tile_connected(connections);
+                let (group, geometry) = calc_geometry(i, &Tile::Grass, connections);
+                let seed = position_hash(index_to_pos(i, self.size.x));
+                let mask = assets
+                    .sprites
+                    .tiles
+                    .mask
+                    .get_tile_variant(connections, seed);
                 let idx = [0, 1, 2, 0, 2, 3];
                 let geometry = geometry.into_iter().zip(idx).map(|(v, i)| v.mask(mask[i]));
-                masked_geometry
-                    .entry(Tile::Grass)
-                    .or_default()
-                    .extend(geometry);
+                masked_geometry.entry(group).or_default().extend(geometry);
             }
 
-            tiles_geometry
-                .entry(*tile)
-                .or_default()
-                .extend(calc_geometry(i, tile, connections));
+            let (group, geometry) = calc_geometry(i, tile, connections);
+            tiles_geometry.entry(group).or_default().extend(geometry);
         }
         let tiles = tiles_geometry
             .into_iter()
-            .map(|(tile, geom)| (tile, ugli::VertexBuffer::new_dynamic(geng.ugli(), geom)))
+            .map(|(group, geom)| (group, ugli::VertexBuffer::new_dynamic(geng.ugli(), geom)))
             .collect();
         let masked = masked_geometry
             .into_iter()
-            .map(|(tile, geom)| (tile, ugli::VertexBuffer::new_dynamic(geng.ugli(), geom)))
+            .map(|(group, geom)| (group, ugli::VertexBuffer::new_dynamic(geng.ugli(), geom)))
             .collect();
         (tiles, masked)
     }
@@ -400,24 +668,48 @@ impl Level {
         .collect()
     }
 
+    /// Loads the level from the levels directory.
+    /// On the web, only the levels saved in the browser storage are available,
+    /// use [Level::load_async] to fetch the bundled ones.
     pub fn load(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
-        let path = run_dir().join("assets").join("levels").join(path);
         #[cfg(not(target_arch = "wasm32"))]
         {
+            let path = run_dir().join("assets").join("levels").join(path);
             let file = std::fs::File::open(path)?;
             let reader = std::io::BufReader::new(file);
             Ok(serde_json::from_reader(reader)?)
         }
         #[cfg(target_arch = "wasm32")]
         {
-            anyhow::bail!("unimplemented")
+            let key = Self::storage_key(path.as_ref());
+            let Some(data) = web::load_string(&key) else {
+                anyhow::bail!("{:?} is not in the browser storage", path.as_ref());
+            };
+            Ok(serde_json::from_str(&data)?)
         }
     }
 
-    pub fn save(&self, path: impl AsRef<std::path::Path>) -> anyhow::Result<()> {
+    /// Loads the level either from the browser storage (on the web)
+    /// or over the asset fetch path.
+    pub async fn load_async(
+        geng: &Geng,
+        path: impl AsRef<std::path::Path>,
+    ) -> anyhow::Result<Self> {
+        // Levels saved in the browser take priority over the bundled ones
+        #[cfg(target_arch = "wasm32")]
+        if let Ok(level) = Self::load(&path) {
+            return Ok(level);
+        }
         let path = run_dir().join("assets").join("levels").join(path);
+        geng::LoadAsset::load(geng, &path).await
+    }
+
+    /// Saves the level into the levels directory.
+    /// On the web, the level is put into the browser storage and downloaded as a file.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> anyhow::Result<()> {
         #[cfg(not(target_arch = "wasm32"))]
         {
+            let path = run_dir().join("assets").join("levels").join(path);
             let file = std::fs::File::create(path)?;
             let writer = std::io::BufWriter::new(file);
             serde_json::to_writer_pretty(writer, self)?;
@@ -425,9 +717,18 @@ impl Level {
         }
         #[cfg(target_arch = "wasm32")]
         {
-            anyhow::bail!("unimplemented")
+            let path = path.as_ref();
+            let data = serde_json::to_string_pretty(self)?;
+            web::save_string(&Self::storage_key(path), &data)?;
+            let file_name = path.file_name().unwrap_or(path.as_os_str());
+            web::download(&file_name.to_string_lossy(), &data)
         }
     }
+
+    #[cfg(target_arch = "wasm32")]
+    fn storage_key(path: &std::path::Path) -> String {
+        format!("drill_above/levels/{}", path.to_string_lossy())
+    }
 }
 
 impl Block {
@@ -438,25 +739,54 @@ impl Block {
             Block::Prop(prop) => prop.sprite.center(),
             Block::Coin(coin) => coin.collider.pos(),
             Block::Spotlight(light) => light.position,
+            Block::PointLight(light) => light.position,
+            Block::CameraZone(zone) => zone.area.center(),
+        }
+    }
+
+    /// Moves the block so that its [Block::position] is at `pos`, the tiles stay in place.
+    pub fn set_position(&mut self, pos: vec2<Coord>) {
+        match self {
+            Block::Tile(_) => {}
+            Block::Hazard(hazard) => hazard.teleport(pos),
+            Block::Prop(prop) => prop.teleport(pos),
+            Block::Coin(coin) => coin.teleport(pos),
+            Block::Spotlight(light) => light.position = pos,
+            Block::PointLight(light) => light.position = pos,
+            Block::CameraZone(zone) => zone.teleport(pos),
+        }
+    }
+
+    /// Moves the block, the tiles move by whole `cells` and the rest by `delta`.
+    pub fn translate(&mut self, cells: vec2<isize>, delta: vec2<Coord>) {
+        match self {
+            Block::Tile((_, pos)) => *pos += cells,
+            Block::Hazard(hazard) => hazard.translate(delta),
+            Block::Prop(prop) => prop.translate(delta),
+            Block::Coin(coin) => coin.translate(delta),
+            Block::Spotlight(light) => light.position += delta,
+            Block::PointLight(light) => light.position += delta,
+            Block::CameraZone(zone) => zone.translate(delta),
         }
     }
 }
 
 impl Hazard {
+    /// Moves the hazard so that its [Block::position] is at `pos`.
     pub fn teleport(&mut self, pos: vec2<Coord>) {
-        self.sprite.translate(pos - self.sprite.bottom_left());
-        self.collider.teleport(pos);
+        self.translate(pos - self.collider.pos());
     }
 
     pub fn translate(&mut self, delta: vec2<Coord>) {
-        self.sprite.translate(delta);
+        self.sprite = self.sprite.translate(delta);
         self.collider.translate(delta);
     }
 }
 
 impl Coin {
+    /// Moves the coin so that its [Block::position] is at `pos`.
     pub fn teleport(&mut self, pos: vec2<Coord>) {
-        self.collider.teleport(pos);
+        self.translate(pos - self.collider.pos());
     }
 
     pub fn translate(&mut self, delta: vec2<Coord>) {
@@ -466,11 +796,11 @@ impl Coin {
 
 impl Prop {
     pub fn teleport(&mut self, pos: vec2<Coord>) {
-        self.sprite.translate(pos - self.sprite.center());
+        self.sprite = self.sprite.translate(pos - self.sprite.center());
     }
 
     pub fn translate(&mut self, delta: vec2<Coord>) {
-        self.sprite.translate(delta);
+        self.sprite = self.sprite.translate(delta);
     }
 }
 
@@ -483,6 +813,8 @@ impl BlockId {
                 | (BlockId::Prop(_), BlockType::Prop(_))
                 | (BlockId::Coin(_), BlockType::Coin)
                 | (BlockId::Spotlight(_), BlockType::Spotlight(_))
+                | (BlockId::PointLight(_), BlockType::PointLight(_))
+                | (BlockId::CameraZone(_), BlockType::CameraZone(_))
         )
     }
 }
@@ -493,6 +825,14 @@ impl Default for Level {
     }
 }
 
+/// Tiles are drawn in groups of the same tile type and animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileGroup {
+    pub tile: Tile,
+    /// Index of the animation in the tileset config.
+    pub animation: Option<usize>,
+}
+
 #[derive(ugli::Vertex, Debug, Clone, Copy)]
 pub struct Vertex {
     pub a_pos: vec2<f32>,
@@ -15,6 +15,49 @@ pub struct SpotlightSource {
     pub intensity: f32,
     pub max_distance: Coord,
     pub volume: f32,
+    /// Radius of the light emitter, the shadows get softer with bigger radius.
+    #[serde(default)]
+    pub source_radius: Coord,
+    #[serde(default)]
+    pub falloff: LightFalloff,
+    /// Portion of the cone near its edge that fades out, from 0 (hard edge) to 1.
+    #[serde(default = "default_angle_softness")]
+    pub angle_softness: f32,
+    #[serde(default)]
+    pub animation: LightAnimation,
+}
+
+/// How the light intensity decreases with the distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LightFalloff {
+    Linear,
+    #[default]
+    Quadratic,
+    /// Smoothstep with a soft start and end.
+    Smooth,
+    /// Physically based inverse square falloff, faded out to reach zero at the max distance.
+    InverseSquare,
+}
+
+fn default_angle_softness() -> f32 {
+    1.0
+}
+
+impl LightFalloff {
+    pub fn all() -> [Self; 4] {
+        use LightFalloff::*;
+        [Linear, Quadratic, Smooth, InverseSquare]
+    }
+
+    /// Index of the falloff in the lighting shaders.
+    pub fn shader_index(self) -> i32 {
+        match self {
+            Self::Linear => 0,
+            Self::Quadratic => 1,
+            Self::Smooth => 2,
+            Self::InverseSquare => 3,
+        }
+    }
 }
 
 impl Default for GlobalLightSource {
@@ -36,6 +79,235 @@ impl Default for SpotlightSource {
             intensity: 0.5,
             max_distance: Coord::new(5.0),
             volume: 0.5,
+            source_radius: Coord::ZERO,
+            falloff: default(),
+            angle_softness: default_angle_softness(),
+            animation: default(),
+        }
+    }
+}
+
+/// Omnidirectional light.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PointLightSource {
+    pub position: vec2<Coord>,
+    pub color: Rgba<f32>,
+    pub intensity: f32,
+    pub max_distance: Coord,
+    pub volume: f32,
+    /// Radius of the light emitter, the shadows get softer with bigger radius.
+    #[serde(default)]
+    pub source_radius: Coord,
+    #[serde(default)]
+    pub falloff: LightFalloff,
+    #[serde(default)]
+    pub animation: LightAnimation,
+}
+
+/// Changes of a light over time. Every effect is disabled by default.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightAnimation {
+    /// Amount of random intensity change, from 0 to 1.
+    pub flicker: f32,
+    /// Intensity changes per second.
+    pub flicker_speed: f32,
+    /// Amount of smooth intensity oscillation, from 0 to 1.
+    pub pulse: f32,
+    pub pulse_period: f32,
+    /// Color to cycle to and back from the light's own color.
+    pub color_cycle: Option<Rgba<f32>>,
+    pub color_period: f32,
+    /// Offset to move to and back from the light's own position.
+    pub path: Option<vec2<Coord>>,
+    pub path_period: f32,
+}
+
+/// A light that follows entities of the level.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AttachedLight {
+    pub target: LightTarget,
+    /// Position of the light relative to the target.
+    /// For the player it is mirrored when facing left.
+    #[serde(default = "vec2_zero")]
+    pub offset: vec2<Coord>,
+    pub light: LightSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightTarget {
+    /// Spotlights attached to the player point where the player is facing or drilling.
+    Player,
+    Coins,
+    Hazards,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum LightSource {
+    Point(PointLightSource),
+    Spot(SpotlightSource),
+}
+
+/// All the lights to render in a frame with their animations applied.
+#[derive(Debug, Clone)]
+pub struct SceneLights {
+    pub global: GlobalLightSource,
+    pub spotlights: Vec<SpotlightSource>,
+    pub point_lights: Vec<PointLightSource>,
+}
+
+fn vec2_zero() -> vec2<Coord> {
+    vec2::ZERO
+}
+
+impl Default for PointLightSource {
+    fn default() -> Self {
+        Self {
+            position: vec2::ZERO,
+            color: Rgba::WHITE,
+            intensity: 0.5,
+            max_distance: Coord::new(5.0),
+            volume: 0.5,
+            source_radius: Coord::ZERO,
+            falloff: default(),
+            animation: default(),
+        }
+    }
+}
+
+impl Default for LightAnimation {
+    fn default() -> Self {
+        Self {
+            flicker: 0.0,
+            flicker_speed: 10.0,
+            pulse: 0.0,
+            pulse_period: 1.0,
+            color_cycle: None,
+            color_period: 1.0,
+            path: None,
+            path_period: 1.0,
+        }
+    }
+}
+
+impl LightAnimation {
+    /// Returns the position offset, the color and the intensity of the light at the given time.
+    /// The `seed` desynchronizes the flicker of different lights.
+    pub fn apply(
+        &self,
+        time: f32,
+        seed: f32,
+        color: Rgba<f32>,
+        intensity: f32,
+    ) -> (vec2<Coord>, Rgba<f32>, f32) {
+        // Goes from 0 to 1 and back over the period.
+        let wave = |period: f32| {
+            if period <= 0.0 {
+                return 0.0;
+            }
+            0.5 - (time / period * f32::PI * 2.0).cos() / 2.0
+        };
+
+        let mut intensity = intensity * (1.0 - self.pulse * wave(self.pulse_period));
+        if self.flicker > 0.0 {
+            // Interpolate random values between the flicker steps.
+            let step = time * self.flicker_speed;
+            let t = step.fract();
+            let noise = |i: f32| {
+                ((i * 12.9898 + seed * 78.233).sin() * 43758.547)
+                    .fract()
+                    .abs()
+            };
+            let noise = noise(step.floor()) * (1.0 - t) + noise(step.floor() + 1.0) * t;
+            intensity *= 1.0 - self.flicker * noise;
+        }
+
+        let color = match self.color_cycle {
+            Some(target) => {
+                let t = wave(self.color_period);
+                Rgba::new(
+                    color.r + (target.r - color.r) * t,
+                    color.g + (target.g - color.g) * t,
+                    color.b + (target.b - color.b) * t,
+                    color.a + (target.a - color.a) * t,
+                )
+            }
+            None => color,
+        };
+
+        let offset = match self.path {
+            Some(path) => path * Coord::new(wave(self.path_period)),
+            None => vec2::ZERO,
+        };
+
+        (offset, color, intensity)
+    }
+}
+
+impl PointLightSource {
+    /// Returns the light with the animation applied.
+    /// The `seed` desynchronizes the animations of different lights, see [light_seed].
+    pub fn animated(&self, time: f32, seed: f32) -> Self {
+        let (offset, color, intensity) =
+            self.animation.apply(time, seed, self.color, self.intensity);
+        Self {
+            position: self.position + offset,
+            color,
+            intensity,
+            ..*self
+        }
+    }
+}
+
+impl SpotlightSource {
+    /// Returns the light with the animation applied.
+    /// The `seed` desynchronizes the animations of different lights, see [light_seed].
+    pub fn animated(&self, time: f32, seed: f32) -> Self {
+        let (offset, color, intensity) =
+            self.animation.apply(time, seed, self.color, self.intensity);
+        Self {
+            position: self.position + offset,
+            color,
+            intensity,
+            ..*self
+        }
+    }
+}
+
+/// Animation seed of a light that stays at the position.
+pub fn light_seed(position: vec2<Coord>) -> f32 {
+    let position = position.map(Coord::as_f32);
+    position.x * 7.13 + position.y * 3.71
+}
+
+impl LightSource {
+    /// Places the light at the given position and rotates spotlights by the angle.
+    pub fn at(&self, position: vec2<Coord>, angle: f32) -> Self {
+        match *self {
+            Self::Point(light) => Self::Point(PointLightSource { position, ..light }),
+            Self::Spot(light) => Self::Spot(SpotlightSource {
+                position,
+                angle: light.angle + angle,
+                ..light
+            }),
+        }
+    }
+}
+
+impl SceneLights {
+    pub fn new(global: GlobalLightSource) -> Self {
+        Self {
+            global,
+            spotlights: Vec::new(),
+            point_lights: Vec::new(),
+        }
+    }
+
+    /// Adds the light with the animation applied.
+    pub fn push(&mut self, light: LightSource, time: f32, seed: f32) {
+        match light {
+            LightSource::Point(light) => self.point_lights.push(light.animated(time, seed)),
+            LightSource::Spot(light) => self.spotlights.push(light.animated(time, seed)),
         }
     }
 }
@@ -0,0 +1,46 @@
+use super::*;
+
+impl Logic<'_> {
+    pub fn process_animation(&mut self) {
+        let player = &self.world.player;
+        let current = player.animation;
+        let sprites = &self.world.assets.sprites.player;
+
+        let state = match player.state {
+            PlayerState::Grounded(_) => {
+                if matches!(
+                    current.state,
+                    PlayerAnimationState::JumpRise | PlayerAnimationState::JumpFall
+                ) {
+                    PlayerAnimationState::Land
+                } else if current.state == PlayerAnimationState::Land
+                    && current.time < sprites.land.duration()
+                {
+                    PlayerAnimationState::Land
+                } else if player.velocity.x.abs() > Coord::new(0.1) {
+                    PlayerAnimationState::Run
+                } else {
+                    PlayerAnimationState::Idle
+                }
+            }
+            PlayerState::WallSliding { .. } => PlayerAnimationState::WallSlide,
+            PlayerState::Airborn => {
+                if player.velocity.y > Coord::ZERO {
+                    PlayerAnimationState::JumpRise
+                } else {
+                    PlayerAnimationState::JumpFall
+                }
+            }
+            PlayerState::Drilling | PlayerState::AirDrill { .. } => PlayerAnimationState::Drill,
+            PlayerState::Respawning { .. } => PlayerAnimationState::Death,
+            PlayerState::Finished { .. } => PlayerAnimationState::Finish,
+        };
+
+        let animation = &mut self.world.player.animation;
+        if animation.state == state {
+            animation.time += self.delta_time;
+        } else {
+            *animation = PlayerAnimation::new(state);
+        }
+    }
+}
@@ -1,10 +1,9 @@
 use super::*;
 
+mod animation;
 mod particles;
 mod player;
 
-use particles::*;
-
 struct Logic<'a> {
     world: &'a mut World,
     player_control: PlayerControl,
@@ -24,22 +23,34 @@ impl World {
 
 impl Logic<'_> {
     fn process(&mut self) {
+        self.world
+            .juice
+            .update(&self.world.assets.juice, self.delta_time);
+        if self.world.juice.is_frozen() {
+            // Hit-stop: freeze the world but keep the camera shaking
+            self.process_camera();
+            return;
+        }
+
         if !matches!(self.world.player.state, PlayerState::Finished { .. }) {
             self.world.time += self.delta_time;
         }
+        if self.world.assists.is_active() {
+            self.world.assists.used = true;
+        }
 
         self.process_player();
         self.process_collisions();
         self.process_particles();
+        self.process_animation();
         self.process_camera();
     }
 
     fn next_level(&mut self) {
-        if let Some(level) = self.world.level.next_level.clone() {
-            self.world.level_transition = Some(level);
-        } else {
-            self.world.level_transition = Some("credits.json".to_string());
-        }
+        self.world.level_transition = Some(match self.world.level.next_level.clone() {
+            Some(level) => LevelTransition::Level(level),
+            None => LevelTransition::Next,
+        });
     }
 
     fn process_collisions(&mut self) {
@@ -47,11 +58,79 @@ impl Logic<'_> {
     }
 
     fn process_camera(&mut self) {
+        let config = &self.world.assets.camera;
+        let player = &self.world.player;
+        let player_pos = player.collider.pos();
+        let state = &mut self.world.camera_state;
+
+        // Vertical dead zone: while grounded, only follow the player
+        // once they leave the zone around the anchor
+        if let PlayerState::Grounded(_) = player.state {
+            state.anchor_y = state.anchor_y.clamp(
+                player_pos.y - config.dead_zone,
+                player_pos.y + config.dead_zone,
+            );
+        } else {
+            state.anchor_y = player_pos.y;
+        }
+
+        // Look ahead in the direction of movement
+        let look_ahead = if let PlayerState::Respawning { .. } = player.state {
+            vec2::ZERO
+        } else {
+            (player.velocity * config.look_ahead_time).clamp_len(..=config.look_ahead_max)
+        };
+        let t = (config.look_ahead_smoothing * self.delta_time).min(Coord::ONE);
+        state.look_ahead += (look_ahead - state.look_ahead) * t;
+
+        let mut target = vec2(player_pos.x, state.anchor_y) + state.look_ahead;
+
+        // Camera zones
+        let zone = self
+            .world
+            .level
+            .camera_zones
+            .iter()
+            .find(|zone| zone.area.contains(player_pos));
+        // Zone zoom is relative to the reference resolution
+        let base_fov = self.world.screen_resolution.y as f32 / PIXELS_PER_UNIT;
+        let fov = base_fov
+            * zone
+                .and_then(|zone| zone.fov)
+                .map_or(1.0, |fov| fov / DEFAULT_FOV);
+        let t = (config.zoom_smoothing * self.delta_time.as_f32()).min(1.0);
+        self.world.camera.fov += (fov - self.world.camera.fov) * t;
+        if let Some(zone) = zone {
+            match zone.mode {
+                CameraZoneMode::Lock => target = zone.area.center(),
+                CameraZoneMode::Limit => {
+                    let view = self.world.camera_view() / Coord::new(2.0);
+                    // Center the view on the axes where the zone is smaller than the view
+                    let limit = |target: Coord, min: Coord, max: Coord, view: Coord| {
+                        if max - min > view * Coord::new(2.0) {
+                            target.clamp(min + view, max - view)
+                        } else {
+                            (min + max) / Coord::new(2.0)
+                        }
+                    };
+                    target = vec2(
+                        limit(target.x, zone.area.min.x, zone.area.max.x, view.x),
+                        limit(target.y, zone.area.min.y, zone.area.max.y, view.y),
+                    );
+                }
+            }
+        }
+
+        let t = (config.smoothing * self.delta_time).min(Coord::ONE);
         let camera_bounds = self.world.camera_bounds();
-        let target = self.world.player.collider.pos();
-        let target = target.clamp_aabb(camera_bounds);
-        let pos = target.map(Coord::as_f32);
-        let pixel = (pos.map(|x| (x * PIXELS_PER_UNIT).round())) / PIXELS_PER_UNIT;
+        let state = &mut self.world.camera_state;
+        state.position += (target - state.position) * t;
+        state.position = state.position.clamp_aabb(camera_bounds);
+
+        // Snap to the pixel grid
+        let pixels_per_unit = self.world.screen_resolution.y as f32 / self.world.camera.fov;
+        let pos = (state.position + self.world.juice.shake_offset).map(Coord::as_f32);
+        let pixel = (pos.map(|x| (x * pixels_per_unit).round())) / pixels_per_unit;
         self.world.camera.center = pixel;
     }
 }
@@ -1,56 +1,46 @@
 use super::*;
 
-#[derive(Debug, Clone)]
-pub struct ParticleSpawn {
-    pub lifetime: Time,
-    pub position: vec2<Coord>,
-    pub velocity: vec2<Coord>,
-    pub amount: usize,
-    pub color: Rgba<f32>,
-    pub radius: Coord,
-    pub radius_range: RangeInclusive<Coord>,
-    pub angle_range: RangeInclusive<Coord>,
-    pub color_range: RangeInclusive<f32>,
-}
-
-impl Default for ParticleSpawn {
-    fn default() -> Self {
-        Self {
-            lifetime: Time::ONE,
-            position: vec2::ZERO,
-            velocity: vec2::ZERO,
-            amount: 1,
-            color: Rgba::WHITE,
-            radius: Coord::ONE,
-            radius_range: Coord::new(0.9)..=Coord::new(1.1),
-            angle_range: Coord::new(-0.5)..=Coord::new(0.5),
-            color_range: -0.05..=0.05,
-        }
-    }
-}
-
 impl Logic<'_> {
-    pub fn spawn_particles(&mut self, config: ParticleSpawn) {
+    /// Spawns particles from the emitter preset in the given direction,
+    /// the direction is scaled by the preset's speed.
+    pub fn emit_particles(&mut self, emitter: &str, position: vec2<Coord>, direction: vec2<Coord>) {
+        let Some(config) = self.world.assets.particles.emitters.get(emitter) else {
+            warn!("Unknown particle emitter {emitter:?}");
+            return;
+        };
         let mut rng = thread_rng();
         for _ in 0..config.amount {
-            let radius = config.radius * rng.gen_range(config.radius_range.clone());
+            let variation = config.radius_variation.abs();
+            let radius = config.radius * (Coord::ONE + rng.gen_range(-variation..=variation));
+            let variation = config.color_variation.abs();
             let color_delta = Rgba::new(
-                rng.gen_range(config.color_range.clone()),
-                rng.gen_range(config.color_range.clone()),
-                rng.gen_range(config.color_range.clone()),
+                rng.gen_range(-variation..=variation),
+                rng.gen_range(-variation..=variation),
+                rng.gen_range(-variation..=variation),
                 0.0,
             );
             let color = config
                 .color
                 .zip_map(color_delta, |s, t| (s + t).clamp(0.0, 1.0));
-            let angle = rng.gen_range(config.angle_range.clone());
-            let velocity = config.velocity.rotate(angle);
+            let spread = config.spread.abs();
+            let angle = rng.gen_range(-spread..=spread);
+            let velocity = (direction * config.speed).rotate(angle);
             self.world.particles.push(Particle {
-                initial_lifetime: config.lifetime,
-                lifetime: config.lifetime,
-                position: config.position,
-                velocity,
-                particle_type: ParticleType::Circle { radius, color },
+                rotation: rng.gen_range(0.0..f32::PI * 2.0),
+                spin: rng.gen_range(-config.spin.abs()..=config.spin.abs()),
+                gravity: config.gravity,
+                drag: config.drag,
+                fade: config.fade,
+                ..Particle::new(
+                    config.lifetime,
+                    position,
+                    velocity,
+                    ParticleType::Shape {
+                        shape: config.shape,
+                        radius,
+                        color,
+                    },
+                )
             });
         }
     }
@@ -58,7 +48,11 @@ impl Logic<'_> {
     pub fn process_particles(&mut self) {
         for particle in &mut self.world.particles {
             particle.lifetime -= self.delta_time;
+            particle.velocity += particle.gravity * self.delta_time;
+            let drag = (Coord::ONE - particle.drag * self.delta_time).max(Coord::ZERO);
+            particle.velocity = particle.velocity * drag;
             particle.position += particle.velocity * self.delta_time;
+            particle.rotation += particle.spin * self.delta_time.as_f32();
         }
         self.world
             .particles
@@ -46,6 +46,7 @@ impl Logic<'_> {
                 sound.stop();
             }
         }
+        self.update_drill_sound();
 
         self.world.player.update_timers(self.delta_time);
 
@@ -164,16 +165,15 @@ impl Logic<'_> {
                 *next_heart -= self.delta_time;
                 if *next_heart <= Time::ZERO {
                     *next_heart += Time::new(0.5);
-                    self.world.particles.push(Particle {
-                        initial_lifetime: Time::new(2.0),
-                        lifetime: Time::new(2.0),
-                        position: self.world.level.finish
+                    self.world.particles.push(Particle::new(
+                        Time::new(2.0),
+                        self.world.level.finish
                             + vec2(Coord::ZERO, self.world.player.collider.raw().height()),
-                        velocity: vec2(0.0, 1.5)
+                        vec2(0.0, 1.5)
                             .rotate(thread_rng().gen_range(-0.5..=0.5))
                             .map(Coord::new),
-                        particle_type: ParticleType::Heart4,
-                    });
+                        ParticleType::Heart4,
+                    ));
                 }
                 self.world.player.velocity += self.world.rules.gravity * self.delta_time;
                 self.world.player.velocity.x = Coord::ZERO;
@@ -201,10 +201,15 @@ impl Logic<'_> {
             return;
         }
 
+        if self.world.assists.infinite_drill_dash {
+            self.world.player.can_drill_dash = true;
+        }
+        let can_drill_dash = self.world.assists.infinite_drill_dash
+            || self.world.rules.can_drill_dash && self.world.player.can_drill_dash;
+
         let mut dash = None;
         let dir = self.player_control.move_dir;
-        if self.world.rules.can_drill_dash && self.world.player.can_drill_dash && dir != vec2::ZERO
-        {
+        if can_drill_dash && dir != vec2::ZERO {
             // Dash
             let dir = dir.normalize_or_zero();
             let vel_dir = self.world.player.velocity.normalize_or_zero();
@@ -235,15 +240,7 @@ impl Logic<'_> {
             self.world.player.can_drill_dash = false;
             dash = Some(self.world.rules.drill_dash_time);
 
-            self.spawn_particles(ParticleSpawn {
-                lifetime: Time::ONE,
-                position: self.world.player.collider.pos(),
-                velocity: -vel_dir * Coord::new(0.5),
-                amount: 5,
-                color: Rgba::opaque(0.8, 0.25, 0.2),
-                radius: Coord::new(0.2),
-                ..Default::default()
-            });
+            self.emit_particles("drill_dash", self.world.player.collider.pos(), -vel_dir);
         } else if !matches!(self.world.player.state, PlayerState::Drilling)
             && self.player_control.drill
             && self.world.level.drill_allowed
@@ -294,18 +291,9 @@ impl Logic<'_> {
         }
 
         // Slow-down punishment
-        let spawn = ParticleSpawn {
-            lifetime: Time::new(0.3),
-            position: player.collider.pos(),
-            velocity: player.velocity,
-            amount: 5,
-            color: Rgba::opaque(0.6, 0.6, 0.6),
-            radius: Coord::new(0.4),
-            angle_range: Coord::new(-0.1)..=Coord::new(0.1),
-            ..Default::default()
-        };
+        let (position, direction) = (player.collider.pos(), player.velocity);
         player.velocity.x = player.velocity.x.clamp_abs(self.world.rules.move_speed);
-        self.spawn_particles(spawn);
+        self.emit_particles("drill_dash_cancel", position, direction);
     }
 
     fn restore_drill_dash(&mut self) {
@@ -317,34 +305,24 @@ impl Logic<'_> {
                 if self.world.player.velocity.x.abs() > Coord::new(0.1)
                     && thread_rng().gen_bool(0.1)
                 {
-                    self.spawn_particles(ParticleSpawn {
-                        lifetime: Time::ONE,
-                        position: self.world.player.collider.feet(),
-                        velocity: vec2(self.world.player.velocity.x.signum(), Coord::ONE)
-                            * Coord::new(0.5),
-                        amount: 2,
-                        color: Rgba::opaque(0.8, 0.8, 0.8),
-                        radius: Coord::new(0.1),
-                        ..Default::default()
-                    });
+                    self.emit_particles(
+                        "walk",
+                        self.world.player.collider.feet(),
+                        vec2(self.world.player.velocity.x.signum(), Coord::ONE),
+                    );
                 }
             }
             PlayerState::WallSliding { wall_normal, .. } => {
                 self.world.player.can_drill_dash = true;
                 if self.world.player.velocity.y < Coord::new(-0.1) && thread_rng().gen_bool(0.1) {
-                    self.spawn_particles(ParticleSpawn {
-                        lifetime: Time::ONE,
-                        position: self.world.player.collider.pos()
+                    self.emit_particles(
+                        "wall_slide",
+                        self.world.player.collider.pos()
                             - wall_normal
                                 * self.world.player.collider.raw().width()
                                 * Coord::new(0.5),
-                        velocity: vec2(wall_normal.x * Coord::new(0.2), Coord::ONE)
-                            * Coord::new(0.5),
-                        amount: 2,
-                        color: Rgba::opaque(0.8, 0.8, 0.8),
-                        radius: Coord::new(0.1),
-                        ..Default::default()
-                    });
+                        vec2(wall_normal.x * Coord::new(0.2), Coord::ONE),
+                    );
                 }
             }
             _ => (),
@@ -428,16 +406,13 @@ impl Logic<'_> {
                 let jump_vel = rules.normal_jump_strength;
                 self.world.player.velocity.y = jump_vel;
                 self.world.player.state = PlayerState::Airborn;
-                self.world.play_sound(&self.world.assets.sounds.jump);
-                self.spawn_particles(ParticleSpawn {
-                    lifetime: Time::ONE,
-                    position: self.world.player.collider.feet(),
-                    velocity: vec2(Coord::ZERO, Coord::ONE),
-                    amount: 3,
-                    color: Rgba::WHITE,
-                    radius: Coord::new(0.1),
-                    ..Default::default()
-                });
+                self.world
+                    .play_sound(Sfx::Jump, self.world.player.collider.pos());
+                self.emit_particles(
+                    "jump",
+                    self.world.player.collider.feet(),
+                    vec2(Coord::ZERO, Coord::ONE),
+                );
             }
             Coyote::Wall { wall_normal } => {
                 let angle = rules.wall_jump_angle * wall_normal.x.signum();
@@ -447,17 +422,14 @@ impl Logic<'_> {
                 player.velocity = jump_vel;
                 player.control_timeout = Some(self.world.rules.wall_jump_timeout);
                 player.state = PlayerState::Airborn;
-                self.world.play_sound(&self.world.assets.sounds.jump);
-                self.spawn_particles(ParticleSpawn {
-                    lifetime: Time::ONE,
-                    position: self.world.player.collider.feet()
+                self.world
+                    .play_sound(Sfx::Jump, self.world.player.collider.pos());
+                self.emit_particles(
+                    "wall_jump",
+                    self.world.player.collider.feet()
                         - wall_normal * self.world.player.collider.raw().width() * Coord::new(0.5),
-                    velocity: jump_vel.normalize_or_zero(),
-                    amount: 3,
-                    color: Rgba::WHITE,
-                    radius: Coord::new(0.1),
-                    ..Default::default()
-                });
+                    jump_vel.normalize_or_zero(),
+                );
             }
             Coyote::DrillJump { direction } => {
                 let rules = &self.world.rules;
@@ -465,16 +437,10 @@ impl Logic<'_> {
                 let current = vec2::dot(self.world.player.velocity, direction);
                 self.world.player.velocity =
                     direction * (current + acceleration).max(rules.drill_jump_speed_min);
-                self.world.play_sound(&self.world.assets.sounds.drill_jump);
-                self.spawn_particles(ParticleSpawn {
-                    lifetime: Time::ONE,
-                    position: self.world.player.collider.pos(),
-                    velocity: direction,
-                    amount: 5,
-                    color: Rgba::opaque(0.8, 0.25, 0.2),
-                    radius: Coord::new(0.3),
-                    ..Default::default()
-                });
+                self.world
+                    .play_sound(Sfx::DrillJump, self.world.player.collider.pos());
+                self.world.juice_event(JuiceEvent::DrillJump);
+                self.emit_particles("drill_jump", self.world.player.collider.pos(), direction);
             }
             Coyote::DrillDirection { .. } => {}
         }
@@ -540,15 +506,11 @@ impl Logic<'_> {
                         && collision.normal.y < Coord::ZERO
                     {
                         if !was_grounded && !has_finished {
-                            particles.push(ParticleSpawn {
-                                lifetime: Time::ONE,
-                                position: player.collider.feet(),
-                                velocity: vec2(Coord::ZERO, Coord::ONE) * Coord::new(0.5),
-                                amount: 3,
-                                color: Rgba::WHITE,
-                                radius: Coord::new(0.1),
-                                ..Default::default()
-                            });
+                            particles.push((
+                                "land",
+                                player.collider.feet(),
+                                vec2(Coord::ZERO, Coord::ONE),
+                            ));
                         }
                         if update_state {
                             player.state = PlayerState::Grounded(tile);
@@ -571,8 +533,8 @@ impl Logic<'_> {
             }
         }
 
-        for spawn in particles {
-            self.spawn_particles(spawn);
+        for (emitter, position, direction) in particles {
+            self.emit_particles(emitter, position, direction);
         }
 
         can_drill
@@ -595,26 +557,15 @@ impl Logic<'_> {
                     Coyote::DrillJump { direction },
                     self.world.rules.coyote_time,
                 ));
-                self.spawn_particles(ParticleSpawn {
-                    lifetime: Time::ONE,
-                    position: self.world.player.collider.pos(),
-                    velocity: direction * Coord::new(0.3),
-                    amount: 8,
-                    color: Rgba::opaque(0.7, 0.7, 0.7),
-                    radius: Coord::new(0.2),
-                    ..Default::default()
-                });
+                self.emit_particles("drill_exit", self.world.player.collider.pos(), direction);
+                self.world.juice_event(JuiceEvent::DrillExit);
             } else if thread_rng().gen_bool(0.2) {
                 // Drilling through the ground
-                self.spawn_particles(ParticleSpawn {
-                    lifetime: Time::ONE,
-                    position: self.world.player.collider.pos(),
-                    velocity: -self.world.player.velocity.normalize_or_zero() * Coord::new(0.5),
-                    amount: 2,
-                    color: Rgba::opaque(0.8, 0.8, 0.8),
-                    radius: Coord::new(0.1),
-                    ..Default::default()
-                });
+                self.emit_particles(
+                    "drill_through",
+                    self.world.player.collider.pos(),
+                    -self.world.player.velocity.normalize_or_zero(),
+                );
             }
         } else if self.world.player.state.is_air_drilling() && can_drill {
             // Entered the ground in drill mode
@@ -628,21 +579,26 @@ impl Logic<'_> {
             self.world.player.velocity = dir * speed.max(self.world.rules.drill_speed_min);
             self.world.player.state = PlayerState::Drilling;
 
-            self.spawn_particles(ParticleSpawn {
-                lifetime: Time::ONE,
-                position: self.world.player.collider.pos(),
-                velocity: -dir * Coord::new(0.3),
-                amount: 5,
-                color: Rgba::opaque(0.7, 0.7, 0.7),
-                radius: Coord::new(0.2),
-                ..Default::default()
-            });
-
-            let sound = self
-                .world
-                .drill_sound
-                .get_or_insert_with(|| self.world.assets.sounds.drill.play());
-            sound.set_volume(self.world.volume);
+            self.emit_particles("drill_enter", self.world.player.collider.pos(), -dir);
+            self.world.juice_event(JuiceEvent::DrillEnter);
+
+            if self.world.drill_sound.is_none() {
+                self.world.drill_sound = Some(self.world.assets.sounds.drill.play());
+                self.update_drill_sound();
+            }
+        }
+    }
+
+    /// Makes the drill loop follow the speed and the position of the player.
+    fn update_drill_sound(&mut self) {
+        let speed = self.world.player.velocity.len();
+        let (pitch, volume) = self.world.assets.audio.drill.get(speed);
+        let volume = volume * self.world.mixer.sfx_volume();
+        let position = self.world.sound_position(self.world.player.collider.pos());
+        if let Some(sound) = &mut self.world.drill_sound {
+            sound.set_speed(pitch);
+            sound.set_volume(volume);
+            sound.set_position(position);
         }
     }
 
@@ -663,15 +619,16 @@ impl Logic<'_> {
             time: Time::new(2.0),
             next_heart: Time::new(0.5),
         };
-        self.world.particles.push(Particle {
-            initial_lifetime: Time::new(2.0),
-            lifetime: Time::new(2.0),
-            position: self.world.player.collider.head()
+        self.world.particles.push(Particle::new(
+            Time::new(2.0),
+            self.world.player.collider.head()
                 + vec2(Coord::ZERO, self.world.player.collider.raw().height()),
-            velocity: vec2(0.0, 1.5).map(Coord::new),
-            particle_type: ParticleType::Heart8,
-        });
-        self.world.play_sound(&self.world.assets.sounds.charm);
+            vec2(0.0, 1.5).map(Coord::new),
+            ParticleType::Heart8,
+        ));
+        self.world
+            .play_sound(Sfx::Charm, self.world.player.collider.pos());
+        self.world.juice_event(JuiceEvent::Finish);
 
         true
     }
@@ -682,26 +639,23 @@ impl Logic<'_> {
         for coin in &mut self.world.level.coins {
             if !coin.collected && self.world.player.collider.check(&coin.collider).is_some() {
                 self.world.coins_collected += 1;
+                self.world.level_coins += 1;
                 coin.collected = true;
                 collected = Some(coin.collider.pos());
             }
         }
         self.world.level.coins.retain(|coin| !coin.collected);
         if let Some(position) = collected {
-            self.world.play_sound(&self.world.assets.sounds.coin);
-            self.spawn_particles(ParticleSpawn {
-                lifetime: Time::ONE,
-                position,
-                velocity: vec2(Coord::ZERO, Coord::ONE) * Coord::new(0.5),
-                amount: 5,
-                color: Rgba::try_from("#e3a912").unwrap(),
-                radius: Coord::new(0.2),
-                ..Default::default()
-            });
+            self.world.play_sound(Sfx::Coin, position);
+            self.emit_particles("coin", position, vec2(Coord::ZERO, Coord::ONE));
         }
     }
 
     fn player_hazards(&mut self) {
+        if self.world.assists.invincible {
+            return;
+        }
+
         // Die from hazards
         for hazard in &self.world.level.hazards {
             if self.world.player.collider.check(&hazard.collider).is_some()
@@ -1,10 +1,15 @@
 use super::*;
 
+mod assists;
+mod audio;
+mod camera;
 mod collider;
 mod grid;
+mod juice;
 mod level;
 mod lights;
 mod logic;
+mod pack;
 mod particle;
 mod player;
 mod static_polygon;
@@ -12,10 +17,15 @@ mod tilemap;
 mod tileset;
 mod world;
 
+pub use assists::*;
+pub use audio::*;
+pub use camera::*;
 pub use collider::*;
 pub use grid::*;
+pub use juice::*;
 pub use level::*;
 pub use lights::*;
+pub use pack::*;
 pub use particle::*;
 pub use player::*;
 pub use static_polygon::*;
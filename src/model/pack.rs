@@ -0,0 +1,56 @@
+use super::*;
+
+/// A sequence of levels grouped into chapters and played in order.
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct LevelPack {
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub chapters: Vec<Chapter>,
+    /// The ending level played after the last chapter.
+    #[serde(default)]
+    pub credits: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    pub name: String,
+    pub levels: Vec<String>,
+}
+
+impl LevelPack {
+    /// Iterates over all levels in the pack in order, excluding the credits.
+    pub fn levels(&self) -> impl Iterator<Item = &String> {
+        self.chapters
+            .iter()
+            .flat_map(|chapter| chapter.levels.iter())
+    }
+
+    pub fn first_level(&self) -> Option<&str> {
+        self.levels().next().map(|level| level.as_str())
+    }
+
+    pub fn is_credits(&self, level: &str) -> bool {
+        self.credits.as_deref() == Some(level)
+    }
+
+    /// Whether the level is played in the pack, including the credits.
+    pub fn contains(&self, level: &str) -> bool {
+        self.levels().any(|name| name == level) || self.is_credits(level)
+    }
+
+    /// Returns the level that follows the given one.
+    /// The last level and the levels outside of the pack are followed by the credits.
+    pub fn next_level(&self, level: &str) -> Option<&str> {
+        let mut levels = self.levels();
+        if levels.any(|name| name == level) {
+            if let Some(next) = levels.next() {
+                return Some(next);
+            }
+        }
+        self.credits.as_deref()
+    }
+}
@@ -6,6 +6,14 @@ pub struct Particle {
     pub lifetime: Time,
     pub position: vec2<Coord>,
     pub velocity: vec2<Coord>,
+    pub rotation: f32,
+    /// Rotation speed in radians per second.
+    pub spin: f32,
+    pub gravity: vec2<Coord>,
+    /// Fraction of the velocity lost per second.
+    pub drag: Coord,
+    /// Whether the particle becomes transparent over its lifetime.
+    pub fade: bool,
     pub particle_type: ParticleType,
 }
 
@@ -13,5 +21,116 @@ pub struct Particle {
 pub enum ParticleType {
     Heart4,
     Heart8,
-    Circle { radius: Coord, color: Rgba<f32> },
+    Shape {
+        shape: ParticleShape,
+        radius: Coord,
+        color: Rgba<f32>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ParticleShape {
+    Circle,
+    Square,
+}
+
+/// Named particle emitter presets.
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct ParticleConfig {
+    pub emitters: HashMap<String, ParticleEmitter>,
+}
+
+/// Describes a burst of particles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleEmitter {
+    #[serde(default = "ParticleEmitter::default_amount")]
+    pub amount: usize,
+    #[serde(default = "ParticleEmitter::default_lifetime")]
+    pub lifetime: Time,
+    /// Multiplier of the emit direction.
+    #[serde(default = "ParticleEmitter::default_speed")]
+    pub speed: Coord,
+    /// Maximum random rotation of the velocity, in radians.
+    #[serde(default = "ParticleEmitter::default_spread")]
+    pub spread: Coord,
+    pub color: Rgba<f32>,
+    /// Maximum random change of each color channel.
+    #[serde(default = "ParticleEmitter::default_color_variation")]
+    pub color_variation: f32,
+    pub radius: Coord,
+    /// Maximum random relative change of the radius.
+    #[serde(default = "ParticleEmitter::default_radius_variation")]
+    pub radius_variation: Coord,
+    #[serde(default = "ParticleEmitter::default_shape")]
+    pub shape: ParticleShape,
+    #[serde(default = "ParticleEmitter::default_gravity")]
+    pub gravity: vec2<Coord>,
+    #[serde(default = "ParticleEmitter::default_drag")]
+    pub drag: Coord,
+    #[serde(default)]
+    pub fade: bool,
+    /// Maximum random rotation speed, in radians per second.
+    #[serde(default)]
+    pub spin: f32,
+}
+
+impl Particle {
+    pub fn new(
+        lifetime: Time,
+        position: vec2<Coord>,
+        velocity: vec2<Coord>,
+        particle_type: ParticleType,
+    ) -> Self {
+        Self {
+            initial_lifetime: lifetime,
+            lifetime,
+            position,
+            velocity,
+            rotation: 0.0,
+            spin: 0.0,
+            gravity: vec2::ZERO,
+            drag: Coord::ZERO,
+            fade: false,
+            particle_type,
+        }
+    }
+}
+
+impl ParticleEmitter {
+    fn default_amount() -> usize {
+        1
+    }
+
+    fn default_lifetime() -> Time {
+        Time::ONE
+    }
+
+    fn default_speed() -> Coord {
+        Coord::ONE
+    }
+
+    fn default_spread() -> Coord {
+        Coord::new(0.5)
+    }
+
+    fn default_color_variation() -> f32 {
+        0.05
+    }
+
+    fn default_radius_variation() -> Coord {
+        Coord::new(0.1)
+    }
+
+    fn default_shape() -> ParticleShape {
+        ParticleShape::Circle
+    }
+
+    fn default_gravity() -> vec2<Coord> {
+        vec2::ZERO
+    }
+
+    fn default_drag() -> Coord {
+        Coord::ZERO
+    }
 }
@@ -22,6 +22,7 @@ pub struct Player {
     pub coyote_time: Option<(Coyote, Time)>,
     pub jump_buffer: Option<Time>,
     pub drill_release: Option<Time>,
+    pub animation: PlayerAnimation,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -45,6 +46,27 @@ pub enum PlayerState {
     },
 }
 
+/// Animation clip currently played by the player.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlayerAnimation {
+    pub state: PlayerAnimationState,
+    /// Time since the clip started.
+    pub time: Time,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PlayerAnimationState {
+    Idle,
+    Run,
+    JumpRise,
+    JumpFall,
+    Land,
+    WallSlide,
+    Drill,
+    Death,
+    Finish,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Coyote {
     Ground,
@@ -72,6 +94,16 @@ impl Player {
             coyote_time: None,
             jump_buffer: None,
             drill_release: None,
+            animation: PlayerAnimation::new(PlayerAnimationState::JumpFall),
+        }
+    }
+}
+
+impl PlayerAnimation {
+    pub fn new(state: PlayerAnimationState) -> Self {
+        Self {
+            state,
+            time: Time::ZERO,
         }
     }
 }
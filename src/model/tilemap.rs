@@ -133,6 +133,27 @@ impl TileMap {
         }
         self.tiles = tiles;
     }
+
+    /// Returns the region of the same tiles connected to the position by the sides.
+    pub fn flood_region(&self, pos: vec2<isize>) -> Vec<vec2<isize>> {
+        let Some(tile) = self.get_tile_isize(pos) else {
+            return vec![];
+        };
+        let mut visited = HashSet::new();
+        visited.insert(pos);
+        let mut stack = vec![pos];
+        let mut region = Vec::new();
+        while let Some(pos) = stack.pop() {
+            region.push(pos);
+            for delta in [vec2(1, 0), vec2(-1, 0), vec2(0, 1), vec2(0, -1)] {
+                let next = pos + delta;
+                if self.get_tile_isize(next) == Some(tile) && visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        region
+    }
 }
 
 pub fn pos_to_index(pos: vec2<usize>, width: usize) -> Option<usize> {
@@ -146,3 +167,12 @@ pub fn pos_to_index(pos: vec2<usize>, width: usize) -> Option<usize> {
 pub fn index_to_pos(index: usize, width: usize) -> vec2<usize> {
     vec2(index % width, index / width)
 }
+
+/// Stable hash of the tile position, used to pick the tile variants.
+pub fn position_hash(pos: vec2<usize>) -> u64 {
+    // SplitMix64 finalizer
+    let mut x = pos.x as u64 ^ (pos.y as u64).rotate_left(32);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
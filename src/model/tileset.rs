@@ -2,6 +2,8 @@ use super::*;
 
 pub struct TileSet {
     texture: ugli::Texture,
+    /// Normal map with the same layout as the texture.
+    normal: Option<ugli::Texture>,
     pub config: TileSetConfig,
 }
 
@@ -9,19 +11,50 @@ pub struct TileSet {
 #[asset(json)]
 pub struct TileSetConfig {
     pub size: vec2<usize>,
-    pub tiles: Vec<([ConnectionFilter; 8], UvRect)>,
+    /// Rules with the same pattern are variants of the same tile.
+    pub tiles: Vec<TileRule>,
+    /// Animations of the tiles used by the rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub animations: Vec<TileAnimation>,
+}
+
+/// A tile used for the neighbourhoods matching the pattern.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileRule {
+    pub pattern: [ConnectionFilter; 8],
+    pub uv: UvRect,
+    /// Relative chance of the rule to be picked among its variants.
+    #[serde(default = "default_weight", skip_serializing_if = "is_default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn is_default_weight(weight: &f32) -> bool {
+    *weight == default_weight()
+}
+
+/// A tile cycling through a sequence of tiles in the tileset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileAnimation {
+    /// Positions of the frames in the tileset counted from the bottom left like the uvs,
+    /// and their durations in seconds.
+    /// The rules using the first frame get animated.
+    pub frames: Vec<(vec2<usize>, f32)>,
 }
 
 type UvRect = [vec2<f32>; 4];
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Connection {
     None,
     Same,
     Other,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ConnectionFilter {
     Some,
     None,
@@ -30,33 +63,89 @@ pub enum ConnectionFilter {
     Other,
 }
 
+/// Result of checking a tileset against every possible neighbourhood.
+#[derive(Debug, Clone, Default)]
+pub struct TileSetCoverage {
+    /// Neighbourhoods that no rule matches.
+    pub unmatched: Vec<[Connection; 8]>,
+    /// Indices of the rules that are never used, because earlier rules match first.
+    /// Variants of the used rules are not shadowed.
+    pub shadowed: Vec<usize>,
+}
+
+impl Connection {
+    pub fn all() -> [Self; 3] {
+        use Connection::*;
+        [None, Same, Other]
+    }
+
+    /// Iterates over every possible combination of the 8 neighbours.
+    pub fn all_patterns() -> impl Iterator<Item = [Self; 8]> {
+        (0..3usize.pow(8)).map(|index| {
+            let mut index = index;
+            [(); 8].map(|_| {
+                let con = Self::all()[index % 3];
+                index /= 3;
+                con
+            })
+        })
+    }
+}
+
 impl TileSet {
-    fn new(mut texture: ugli::Texture, config: TileSetConfig) -> Self {
+    fn new(
+        mut texture: ugli::Texture,
+        mut normal: Option<ugli::Texture>,
+        config: TileSetConfig,
+    ) -> Self {
         texture.set_filter(ugli::Filter::Nearest);
-        Self { texture, config }
+        if let Some(normal) = &mut normal {
+            normal.set_filter(ugli::Filter::Nearest);
+        }
+        Self {
+            texture,
+            normal,
+            config,
+        }
     }
 
     pub fn texture(&self) -> &ugli::Texture {
         &self.texture
     }
 
+    pub fn normal_texture(&self) -> Option<&ugli::Texture> {
+        self.normal.as_ref()
+    }
+
     pub fn get_tile_connected(&self, connections: [Connection; 8]) -> UvRect {
-        let con_match = |pattern: &[ConnectionFilter; 8]| {
-            connections
-                .iter()
-                .zip(pattern)
-                .all(|(&con, pat)| pat.matches(con))
-        };
+        self.config.get_tile_connected(connections)
+    }
 
-        self.config
-            .tiles
-            .iter()
-            .find_map(|(pattern, uv)| con_match(pattern).then_some(uv).copied())
-            .unwrap_or_else(|| {
-                error!("Failed to find the uv for {:?}", connections);
-                self.config.tiles.first().unwrap().1
-            })
-        // .expect("Failed to find a suitable tile")
+    pub fn get_tile_variant(&self, connections: [Connection; 8], seed: u64) -> UvRect {
+        self.config.get_tile_variant(connections, seed)
+    }
+}
+
+impl TileAnimation {
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|(_, delay)| delay).sum()
+    }
+
+    /// Returns the position of the frame at the given time, looping the animation.
+    pub fn frame(&self, time: Time) -> Option<vec2<usize>> {
+        let duration = self.duration();
+        let mut time = if duration > 0.0 {
+            time.as_f32().rem_euclid(duration)
+        } else {
+            0.0
+        };
+        for &(pos, delay) in &self.frames {
+            if time < delay {
+                return Some(pos);
+            }
+            time -= delay;
+        }
+        self.frames.last().map(|&(pos, _)| pos)
     }
 }
 
@@ -77,8 +166,102 @@ pub fn get_tile_uv_xy(pos: vec2<usize>, set_size: vec2<usize>) -> UvRect {
 }
 
 impl TileSetConfig {
-    pub fn generate_from(texture: &image::RgbaImage, size: vec2<usize>) -> Self {
-        let tiles = {
+    /// Finds the uv of the first tile matching the connections.
+    pub fn get_tile_connected(&self, connections: [Connection; 8]) -> UvRect {
+        self.get_tile_variant(connections, 0)
+    }
+
+    /// Finds the uv of a tile matching the connections.
+    /// The variant is picked by the `seed`, so the same seed always gives the same tile.
+    pub fn get_tile_variant(&self, connections: [Connection; 8], seed: u64) -> UvRect {
+        let Some(first) = self.find_tile(connections) else {
+            error!("Failed to find the uv for {:?}", connections);
+            return self.tiles.first().unwrap().uv;
+        };
+
+        let variants: Vec<usize> = self.variants(first).collect();
+        let total: f32 = variants.iter().map(|&i| self.tiles[i].weight).sum();
+        if variants.len() == 1 || total <= 0.0 {
+            return self.tiles[first].uv;
+        }
+        // Top 24 bits of the seed as a number in 0..1
+        let mut pick = (seed >> 40) as f32 / (1 << 24) as f32 * total;
+        for &i in &variants {
+            pick -= self.tiles[i].weight;
+            if pick < 0.0 {
+                return self.tiles[i].uv;
+            }
+        }
+        self.tiles[*variants.last().unwrap()].uv
+    }
+
+    /// Iterates over the rules with the same pattern as the given one, including itself.
+    pub fn variants(&self, rule: usize) -> impl Iterator<Item = usize> + '_ {
+        let pattern = self.tiles[rule].pattern;
+        (rule..self.tiles.len()).filter(move |&i| self.tiles[i].pattern == pattern)
+    }
+
+    /// Returns the index of the animation starting at the tile with the given uv.
+    pub fn find_animation(&self, uv: UvRect) -> Option<usize> {
+        let pos = (uv[0] * self.size.map(|x| x as f32)).map(|x| x.round() as usize);
+        self.animations
+            .iter()
+            .position(|animation| animation.frames.first().map(|&(first, _)| first) == Some(pos))
+    }
+
+    /// Returns the uv offset from the first frame of the animation to the current one.
+    pub fn animation_offset(&self, animation: Option<usize>, time: Time) -> vec2<f32> {
+        animation
+            .and_then(|i| self.animations.get(i))
+            .and_then(|animation| Some((animation.frames.first()?.0, animation.frame(time)?)))
+            .map_or(vec2::ZERO, |(first, frame)| {
+                (frame.map(|x| x as f32) - first.map(|x| x as f32)) / self.size.map(|x| x as f32)
+            })
+    }
+
+    /// Returns the index of the first rule matching the connections.
+    pub fn find_tile(&self, connections: [Connection; 8]) -> Option<usize> {
+        self.tiles.iter().position(|rule| {
+            connections
+                .iter()
+                .zip(&rule.pattern)
+                .all(|(&con, pat)| pat.matches(con))
+        })
+    }
+
+    /// Checks which neighbourhoods are not covered by the rules
+    /// and which rules are never used.
+    pub fn check_coverage(&self) -> TileSetCoverage {
+        let mut used = vec![false; self.tiles.len()];
+        let mut unmatched = Vec::new();
+        for connections in Connection::all_patterns() {
+            match self.find_tile(connections) {
+                Some(first) => {
+                    for i in self.variants(first) {
+                        used[i] = true;
+                    }
+                }
+                None => unmatched.push(connections),
+            }
+        }
+        let shadowed = used
+            .into_iter()
+            .enumerate()
+            .filter(|(_, used)| !used)
+            .map(|(i, _)| i)
+            .collect();
+        TileSetCoverage {
+            unmatched,
+            shadowed,
+        }
+    }
+
+    /// Generates the rules from the markers on the edges of the tiles in the config image.
+    /// Tiles with the same markers become variants of each other,
+    /// and the opacity of the center pixel sets the weight of the variant.
+    pub fn generate_from(texture: &image::RgbaImage, size: vec2<usize>) -> anyhow::Result<Self> {
+        let mut tiles = Vec::new();
+        {
             let size = size.map(|x| x as u32);
             let (w, h) = texture.dimensions();
             let texture_size = vec2(w, h);
@@ -94,44 +277,59 @@ impl TileSetConfig {
                 (0, 1),
             ]
             .map(|(x, y)| vec2(x, y) * (tile_size - vec2(1, 1)) / 2);
-            (0..size.y)
-                .flat_map(|y| {
-                    (0..size.x).filter_map(move |x| {
-                        let pos = vec2(x, y) * tile_size;
-                        let cons = positions.map(|d| {
-                            let pos = pos + d;
-                            let pixel = texture.get_pixel(pos.x, texture_size.y - pos.y - 1);
-                            ConnectionFilter::from_color(pixel.0)
-                        });
-                        let mut connections = [ConnectionFilter::None; 8];
-                        for i in 0..8 {
-                            let Some(con) = cons[i] else {
-                                return None;
-                            };
-                            connections[i] = con;
-                        }
-                        let uv =
-                            get_tile_uv_xy(vec2(x as usize, y as usize), size.map(|x| x as usize));
-                        Some((connections, uv))
-                    })
-                })
-                .collect()
-        };
-        Self { size, tiles }
+            for y in 0..size.y {
+                'tile: for x in 0..size.x {
+                    let pos = vec2(x, y) * tile_size;
+                    let mut connections = [ConnectionFilter::None; 8];
+                    for (connection, d) in connections.iter_mut().zip(positions) {
+                        let pos = pos + d;
+                        let pixel = (pos.x, texture_size.y - pos.y - 1);
+                        let color = texture.get_pixel(pixel.0, pixel.1).0;
+                        let con = ConnectionFilter::from_color(color).map_err(|err| {
+                            anyhow::anyhow!("{err} at pixel {pixel:?} of the tile ({x}, {y})")
+                        })?;
+                        // Tiles with transparent markers are not used
+                        let Some(con) = con else {
+                            continue 'tile;
+                        };
+                        *connection = con;
+                    }
+                    let center = pos + (tile_size - vec2(1, 1)) / 2;
+                    let alpha = texture.get_pixel(center.x, texture_size.y - center.y - 1).0[3];
+                    // Unmarked centers keep the default weight
+                    let weight = if alpha == 0 {
+                        default_weight()
+                    } else {
+                        alpha as f32 / 255.0
+                    };
+                    let uv = get_tile_uv_xy(vec2(x as usize, y as usize), size.map(|x| x as usize));
+                    tiles.push(TileRule {
+                        pattern: connections,
+                        uv,
+                        weight,
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            size,
+            tiles,
+            animations: Vec::new(),
+        })
     }
 }
 
 impl ConnectionFilter {
-    fn from_color(color: [u8; 4]) -> Option<Self> {
-        match color {
+    fn from_color(color: [u8; 4]) -> anyhow::Result<Option<Self>> {
+        Ok(match color {
             [_, _, _, 0] => None,
             [255, 0, 255, _] => Some(Self::Some),
             [255, 255, 255, _] => Some(Self::None),
             [0, 255, 0, _] => Some(Self::Any),
             [0, 0, 255, _] => Some(Self::Same),
             [255, 0, 0, _] => Some(Self::Other),
-            _ => panic!("unknown color: {color:?}"),
-        }
+            _ => anyhow::bail!("Unknown color {color:?}"),
+        })
     }
 
     fn matches(&self, connection: Connection) -> bool {
@@ -153,9 +351,14 @@ impl geng::LoadAsset for TileSet {
             let mut texture = ugli::Texture::load(&geng, &path).await?;
             texture.set_filter(ugli::Filter::Nearest);
             let name = path.file_stem().unwrap().to_str().unwrap();
-            let path = path.parent().unwrap().join(format!("{name}_config.json"));
-            let config = TileSetConfig::load(&geng, &path).await?;
-            Ok(Self::new(texture, config))
+            let dir = path.parent().unwrap();
+            let config_path = dir.join(format!("{name}_config.json"));
+            let config = TileSetConfig::load(&geng, &config_path).await?;
+            // The normal map is optional
+            let normal = ugli::Texture::load(&geng, &dir.join(format!("{name}_normal.png")))
+                .await
+                .ok();
+            Ok(Self::new(texture, normal, config))
         }
         .boxed_local()
     }
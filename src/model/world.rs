@@ -30,70 +30,260 @@ pub struct Rules {
     pub drill_jump_speed_inc: Coord,
 }
 
+/// Level-specific overrides of the global [Rules].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RulesPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gravity: Option<vec2<Coord>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_speed: Option<Coord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normal_jump_strength: Option<Coord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_jump_strength: Option<Coord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free_fall_speed: Option<Coord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub can_drill_dash: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drill_speed_min: Option<Coord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drill_dash_speed_min: Option<Coord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drill_jump_speed_min: Option<Coord>,
+}
+
+#[derive(Debug, Clone)]
+pub enum LevelTransition {
+    /// Go to the next level in the level pack.
+    Next,
+    /// Go to the specific level.
+    Level(String),
+}
+
 pub struct World {
     pub assets: Rc<Assets>,
     pub rules: Rules,
-    pub volume: f64,
+    pub assists: Assists,
+    pub juice: Juice,
+    pub mixer: Mixer,
     pub camera: Camera2d,
+    /// Resolution of the pixel screen the world is rendered to.
+    pub screen_resolution: vec2<usize>,
+    pub camera_state: CameraState,
     pub geometry: (
-        HashMap<Tile, ugli::VertexBuffer<Vertex>>,
-        HashMap<Tile, ugli::VertexBuffer<MaskedVertex>>,
+        HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
     ),
     pub light_geometry: Vec<StaticPolygon>,
     pub level: Level,
-    pub level_transition: Option<String>,
+    pub level_transition: Option<LevelTransition>,
     pub player: Player,
     pub particles: Vec<Particle>,
+    /// Coins collected over the whole run.
     pub coins_collected: usize,
+    /// Coins collected in the current level, included in `coins_collected`.
+    pub level_coins: usize,
     pub drill_sound: Option<geng::SoundEffect>,
     pub time: Time,
     pub deaths: usize,
 }
 
+impl Rules {
+    /// Loads the rules from `assets/rules.json`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> anyhow::Result<Self> {
+        let path = run_dir().join("assets").join("rules.json");
+        let file = std::fs::File::open(path)?;
+        let reader = std::io::BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+impl RulesPatch {
+    pub fn is_empty(&self) -> bool {
+        let Self {
+            gravity,
+            move_speed,
+            normal_jump_strength,
+            wall_jump_strength,
+            free_fall_speed,
+            can_drill_dash,
+            drill_speed_min,
+            drill_dash_speed_min,
+            drill_jump_speed_min,
+        } = self;
+        gravity.is_none()
+            && move_speed.is_none()
+            && normal_jump_strength.is_none()
+            && wall_jump_strength.is_none()
+            && free_fall_speed.is_none()
+            && can_drill_dash.is_none()
+            && drill_speed_min.is_none()
+            && drill_dash_speed_min.is_none()
+            && drill_jump_speed_min.is_none()
+    }
+
+    /// Overrides the rules with the values set in the patch.
+    pub fn apply(&self, mut rules: Rules) -> Rules {
+        let Self {
+            gravity,
+            move_speed,
+            normal_jump_strength,
+            wall_jump_strength,
+            free_fall_speed,
+            can_drill_dash,
+            drill_speed_min,
+            drill_dash_speed_min,
+            drill_jump_speed_min,
+        } = *self;
+        rules.gravity = gravity.unwrap_or(rules.gravity);
+        rules.move_speed = move_speed.unwrap_or(rules.move_speed);
+        rules.normal_jump_strength = normal_jump_strength.unwrap_or(rules.normal_jump_strength);
+        rules.wall_jump_strength = wall_jump_strength.unwrap_or(rules.wall_jump_strength);
+        rules.free_fall_speed = free_fall_speed.unwrap_or(rules.free_fall_speed);
+        rules.can_drill_dash = can_drill_dash.unwrap_or(rules.can_drill_dash);
+        rules.drill_speed_min = drill_speed_min.unwrap_or(rules.drill_speed_min);
+        rules.drill_dash_speed_min = drill_dash_speed_min.unwrap_or(rules.drill_dash_speed_min);
+        rules.drill_jump_speed_min = drill_jump_speed_min.unwrap_or(rules.drill_jump_speed_min);
+        rules
+    }
+}
+
 impl World {
     pub fn new(geng: &Geng, assets: &Rc<Assets>, rules: Rules, level: Level) -> Self {
+        let (screen_resolution, _) = assets.screen.fit(geng.window().size());
         Self {
             assets: assets.clone(),
-            volume: 0.5,
+            assists: default(),
+            juice: Juice::new(default()),
+            mixer: default(),
             camera: Camera2d {
                 center: vec2(0.0, 0.25),
                 rotation: 0.0,
-                fov: 22.5,
+                fov: screen_resolution.y as f32 / PIXELS_PER_UNIT,
             },
+            screen_resolution,
+            camera_state: CameraState::new(level.spawn_point),
             geometry: level.calculate_geometry(geng, assets),
             light_geometry: level.calculate_light_geometry(geng),
             player: Player::new(level.spawn_point),
             particles: default(),
             level_transition: None,
             coins_collected: 0,
+            level_coins: 0,
             time: Time::ZERO,
             drill_sound: None,
             deaths: 0,
-            rules,
+            rules: level.rules.apply(rules),
             level,
         }
     }
 
-    pub fn play_sound(&self, sound: &geng::Sound) {
-        let mut sound = sound.play();
-        sound.set_volume(self.volume);
+    /// Replaces the level keeping the player and the progress.
+    /// All coins of the new level are uncollected, so the ones collected in it are taken back.
+    pub fn set_level(&mut self, geng: &Geng, level: Level) {
+        self.coins_collected -= std::mem::take(&mut self.level_coins);
+        self.geometry = level.calculate_geometry(geng, &self.assets);
+        self.light_geometry = level.calculate_light_geometry(geng);
+        self.level = level;
+    }
+
+    /// Sets the rules with the level's overrides applied on top.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = self.level.rules.apply(rules);
+    }
+
+    /// Replaces the assets and rebuilds the geometry that depends on them.
+    pub fn set_assets(&mut self, geng: &Geng, assets: &Rc<Assets>) {
+        self.assets = assets.clone();
+        self.geometry = self.level.calculate_geometry(geng, assets);
+    }
+
+    /// Plays the sound effect panned by the position on the screen.
+    pub fn play_sound(&self, sfx: Sfx, position: vec2<Coord>) {
+        let mut sound = self.assets.sounds.get_sfx(sfx).play();
+        sound.set_volume(self.mixer.sfx_volume());
+        if let Some(&variation) = self.assets.audio.pitch_variation.get(&sfx) {
+            let variation = variation.abs();
+            sound.set_speed(1.0 + thread_rng().gen_range(-variation..=variation));
+        }
+        sound.set_position(self.sound_position(position));
+    }
+
+    /// Position of the sound relative to the listener
+    /// so that it is panned by its position on the screen.
+    pub fn sound_position(&self, position: vec2<Coord>) -> vec3<f64> {
+        let offset = (position.x.as_f32() - self.camera.center.x) as f64;
+        let half_width = (self.camera_view().x.as_f32() / 2.0) as f64;
+        let pan = (offset / half_width).clamp(-1.0, 1.0) * self.assets.audio.pan;
+        // In front of the listener
+        vec3(pan, 0.0, -1.0)
     }
 
     pub fn kill_player(&mut self) {
         self.player.velocity = vec2::ZERO;
         self.player.state = PlayerState::Respawning { time: Time::ONE };
         self.deaths += 1;
-        self.play_sound(&self.assets.sounds.death);
+        self.play_sound(Sfx::Death, self.player.collider.pos());
+        self.juice_event(JuiceEvent::Death);
+    }
+
+    pub fn juice_event(&mut self, event: JuiceEvent) {
+        self.juice.trigger(&self.assets.juice, event);
+    }
+
+    /// Changes the resolution keeping the scale of the camera.
+    pub fn set_screen_resolution(&mut self, resolution: vec2<usize>) {
+        if resolution != self.screen_resolution {
+            self.camera.fov *= resolution.y as f32 / self.screen_resolution.y as f32;
+            self.screen_resolution = resolution;
+        }
+    }
+
+    /// Lights to render this frame.
+    pub fn scene_lights(&self) -> SceneLights {
+        let player = &self.player;
+        let angle = if player.state.is_drilling() {
+            player.velocity.map(Coord::as_f32).arg()
+        } else if player.facing_left {
+            f32::PI
+        } else {
+            0.0
+        };
+        self.level
+            .scene_lights(self.time.as_f32(), (player.collider.pos(), angle))
+    }
+
+    /// Size of the area visible by the camera.
+    pub fn camera_view(&self) -> vec2<Coord> {
+        let resolution = self.screen_resolution.map(|x| x as f32);
+        vec2(
+            self.camera.fov * resolution.x / resolution.y,
+            self.camera.fov,
+        )
+        .map(Coord::new)
     }
 
     pub fn camera_bounds(&self) -> Aabb2<Coord> {
         let mut level_bounds = self.level.bounds();
         level_bounds.min.y += self.level.grid.cell_size.y * Coord::new(0.5);
-        let camera_view =
-            (vec2(self.camera.fov * (16.0 / 9.0), self.camera.fov) / 2.0).map(Coord::new); // TODO: remove hardcode
-        Aabb2::from_corners(
-            level_bounds.bottom_left() + camera_view,
-            level_bounds.top_right() - camera_view,
-        )
+        let camera_view = self.camera_view();
+        // Center the camera on the axes where the level is smaller than the view
+        let axis = |min: Coord, max: Coord, view: Coord| {
+            if max - min < view {
+                let center = (min + max) / Coord::new(2.0);
+                (center, center)
+            } else {
+                let half = view / Coord::new(2.0);
+                (min + half, max - half)
+            }
+        };
+        let (min_x, max_x) = axis(level_bounds.min.x, level_bounds.max.x, camera_view.x);
+        let (min_y, max_y) = axis(level_bounds.min.y, level_bounds.max.y, camera_view.y);
+        Aabb2 {
+            min: vec2(min_x, min_y),
+            max: vec2(max_x, max_y),
+        }
     }
 }
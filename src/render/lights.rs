@@ -4,6 +4,7 @@ pub struct LightsRender {
     geng: Geng,
     assets: Rc<Assets>,
     buffers: Buffers,
+    post: PostRender,
 }
 
 /// All buffers used in the lighting pipeline.
@@ -28,6 +29,7 @@ impl LightsRender {
             geng: geng.clone(),
             assets: assets.clone(),
             buffers: Buffers::new(geng),
+            post: PostRender::new(geng, assets),
         }
     }
 
@@ -52,7 +54,8 @@ impl LightsRender {
 
     pub fn finish_render(
         &mut self,
-        level: &Level,
+        lights: &SceneLights,
+        grade: Option<&str>,
         geometry: &[StaticPolygon],
         camera: &Camera2d,
         framebuffer: &mut ugli::Framebuffer,
@@ -61,28 +64,28 @@ impl LightsRender {
         self.render_normal_map(camera, geometry);
 
         // Render lights
-        self.render_lights(level, camera, geometry);
+        self.render_lights(lights, camera, geometry);
 
         // Draw the texture to the screen
-        self.geng.draw_2d(
-            framebuffer,
-            &geng::PixelPerfectCamera,
-            &draw_2d::TexturedQuad::new(
-                Aabb2::ZERO.extend_positive(framebuffer.size().map(|x| x as f32)),
-                &self.buffers.postprocess_texture,
-            ),
-        );
+        self.post
+            .apply(&self.buffers.postprocess_texture, grade, framebuffer);
     }
 
     /// Renders the world for each light separately onto the postprocessing texture.
-    pub fn render_lights(&mut self, level: &Level, camera: &Camera2d, geometry: &[StaticPolygon]) {
-        self.render_global_light(level);
-        self.render_spotlights(level, camera, geometry);
+    pub fn render_lights(
+        &mut self,
+        lights: &SceneLights,
+        camera: &Camera2d,
+        geometry: &[StaticPolygon],
+    ) {
+        self.render_global_light(&lights.global);
+        self.render_spotlights(&lights.spotlights, camera, geometry);
+        self.render_point_lights(&lights.point_lights, camera, geometry);
     }
 
     /// Renders the world for the global light onto the postprocessing texture.
     /// Should be called as the first light render as it clears the texture.
-    pub fn render_global_light(&mut self, level: &Level) {
+    pub fn render_global_light(&mut self, light: &GlobalLightSource) {
         let mut world_framebuffer =
             attach_texture(&mut self.buffers.postprocess_texture, &self.geng);
         let framebuffer_size = world_framebuffer.size();
@@ -97,8 +100,8 @@ impl LightsRender {
             ugli::uniforms! {
                 u_framebuffer_size: framebuffer_size,
                 u_source_texture: &self.buffers.world_texture,
-                u_light_color: level.global_light.color,
-                u_light_intensity: level.global_light.intensity,
+                u_light_color: light.color,
+                u_light_intensity: light.intensity,
             },
             ugli::DrawParameters {
                 blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
@@ -112,123 +115,190 @@ impl LightsRender {
 
     pub fn render_spotlights(
         &mut self,
-        level: &Level,
+        spotlights: &[SpotlightSource],
         camera: &Camera2d,
         geometry: &[StaticPolygon],
     ) {
-        for spotlight in &level.spotlights {
-            // Using `world_texture` here but it is not actually used by the shader
-            let mut light_framebuffer = ugli::Framebuffer::new(
-                self.geng.ugli(),
-                ugli::ColorAttachment::Texture(&mut self.buffers.world_texture),
-                ugli::DepthAttachment::RenderbufferWithStencil(&mut self.buffers.shadow_stencil),
-            );
-            let framebuffer_size = light_framebuffer.size().map(|x| x as f32);
-            ugli::clear(&mut light_framebuffer, None, None, Some(0));
+        for spotlight in spotlights {
+            // Soft shadows are approximated by rendering the light several times
+            // with the shadows cast from different points of the emitter.
+            let samples = shadow_samples(spotlight.source_radius);
+            let sample_weight = 1.0 / samples.len() as f32;
+            for sample in samples {
+                self.render_shadows(spotlight.position + sample, camera, geometry);
+                self.render_spotlight(spotlight, sample_weight, camera);
+            }
+        }
+    }
 
-            // Shadow map
-            for polygon in geometry {
-                // Cast shadow
-                ugli::draw(
-                    &mut light_framebuffer,
-                    &self.assets.shaders.point_light_shadow_map,
-                    ugli::DrawMode::TriangleFan,
-                    &polygon.doubled,
-                    (
-                        ugli::uniforms! {
-                            u_model_matrix: mat3::identity(),
-                            u_light_pos: spotlight.position.map(Coord::as_f32),
-                        },
-                        geng::camera2d_uniforms(camera, framebuffer_size),
-                    ),
-                    ugli::DrawParameters {
-                        // Just in case the shader writes something in the texture,
-                        // discard it during blending.
-                        blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
-                            src_factor: ugli::BlendFactor::Zero,
-                            dst_factor: ugli::BlendFactor::One,
-                        })),
-                        // Increment the shadow casters count
-                        stencil_mode: Some(ugli::StencilMode::always(ugli::FaceStencilMode {
-                            test: ugli::StencilTest {
-                                condition: ugli::Condition::Always,
-                                reference: 0,
-                                mask: 0xFF,
-                            },
-                            op: ugli::StencilOp::always(ugli::StencilOpFunc::Increment),
-                        })),
-                        ..Default::default()
-                    },
-                );
-                // Remove self-shadow
-                ugli::draw(
-                    &mut light_framebuffer,
-                    &self.assets.shaders.shadow_remove,
-                    ugli::DrawMode::TriangleFan,
-                    &polygon.vertices,
-                    (
-                        ugli::uniforms! {
-                            u_model_matrix: mat3::identity(),
-                            u_color: Rgba::TRANSPARENT_BLACK,
-                        },
-                        geng::camera2d_uniforms(camera, framebuffer_size),
-                    ),
-                    ugli::DrawParameters {
-                        // Decrement the shadow casters count
-                        stencil_mode: Some(ugli::StencilMode::always(ugli::FaceStencilMode {
-                            test: ugli::StencilTest {
-                                condition: ugli::Condition::Always,
-                                reference: 0,
-                                mask: 0xFF,
-                            },
-                            op: ugli::StencilOp::always(ugli::StencilOpFunc::Decrement),
-                        })),
-                        ..Default::default()
-                    },
-                );
+    /// Renders the world lit by the spotlight outside of the shadows in the stencil buffer.
+    fn render_spotlight(
+        &mut self,
+        spotlight: &SpotlightSource,
+        sample_weight: f32,
+        camera: &Camera2d,
+    ) {
+        // Render the world for that light
+        let mut world_framebuffer = ugli::Framebuffer::new(
+            self.geng.ugli(),
+            ugli::ColorAttachment::Texture(&mut self.buffers.postprocess_texture),
+            ugli::DepthAttachment::RenderbufferWithStencil(&mut self.buffers.shadow_stencil),
+        );
+        let framebuffer_size = world_framebuffer.size().map(|x| x as f32);
+        ugli::draw(
+            &mut world_framebuffer,
+            &self.assets.shaders.spotlight,
+            ugli::DrawMode::TriangleFan,
+            &self.buffers.quad_geometry,
+            (
+                ugli::uniforms! {
+                    u_model_matrix: mat3::identity(),
+                    u_light_pos: spotlight.position.map(Coord::as_f32),
+                    u_light_angle: spotlight.angle,
+                    u_light_angle_range: spotlight.angle_range,
+                    u_light_color: spotlight.color,
+                    u_light_intensity: spotlight.intensity,
+                    u_light_max_distance: spotlight.max_distance.as_f32(),
+                    u_light_volume: spotlight.volume,
+                    u_light_falloff: spotlight.falloff.shader_index(),
+                    u_light_angle_softness: spotlight.angle_softness,
+                    u_light_sample_weight: sample_weight,
+                    u_normal_texture: &self.buffers.normal_texture,
+                    u_source_texture: &self.buffers.world_texture,
+                    u_framebuffer_size: self.buffers.normal_texture.size(),
+                },
+                geng::camera2d_uniforms(camera, framebuffer_size),
+            ),
+            lit_draw_parameters(),
+        );
+    }
+
+    pub fn render_point_lights(
+        &mut self,
+        point_lights: &[PointLightSource],
+        camera: &Camera2d,
+        geometry: &[StaticPolygon],
+    ) {
+        for light in point_lights {
+            let samples = shadow_samples(light.source_radius);
+            let sample_weight = 1.0 / samples.len() as f32;
+            for sample in samples {
+                self.render_shadows(light.position + sample, camera, geometry);
+                self.render_point_light(light, sample_weight, camera);
             }
+        }
+    }
 
-            // Render the world for that light
-            let mut world_framebuffer = ugli::Framebuffer::new(
-                self.geng.ugli(),
-                ugli::ColorAttachment::Texture(&mut self.buffers.postprocess_texture),
-                ugli::DepthAttachment::RenderbufferWithStencil(&mut self.buffers.shadow_stencil),
-            );
-            let framebuffer_size = world_framebuffer.size().map(|x| x as f32);
+    /// Renders the world lit by the point light outside of the shadows in the stencil buffer.
+    fn render_point_light(
+        &mut self,
+        light: &PointLightSource,
+        sample_weight: f32,
+        camera: &Camera2d,
+    ) {
+        // Render the world for that light
+        let mut world_framebuffer = ugli::Framebuffer::new(
+            self.geng.ugli(),
+            ugli::ColorAttachment::Texture(&mut self.buffers.postprocess_texture),
+            ugli::DepthAttachment::RenderbufferWithStencil(&mut self.buffers.shadow_stencil),
+        );
+        let framebuffer_size = world_framebuffer.size().map(|x| x as f32);
+        ugli::draw(
+            &mut world_framebuffer,
+            &self.assets.shaders.point_light,
+            ugli::DrawMode::TriangleFan,
+            &self.buffers.quad_geometry,
+            (
+                ugli::uniforms! {
+                    u_model_matrix: mat3::identity(),
+                    u_light_pos: light.position.map(Coord::as_f32),
+                    u_light_color: light.color,
+                    u_light_intensity: light.intensity,
+                    u_light_max_distance: light.max_distance.as_f32(),
+                    u_light_volume: light.volume,
+                    u_light_falloff: light.falloff.shader_index(),
+                    u_light_sample_weight: sample_weight,
+                    u_normal_texture: &self.buffers.normal_texture,
+                    u_source_texture: &self.buffers.world_texture,
+                    u_framebuffer_size: self.buffers.normal_texture.size(),
+                },
+                geng::camera2d_uniforms(camera, framebuffer_size),
+            ),
+            lit_draw_parameters(),
+        );
+    }
+
+    /// Counts the shadow casters between the light and each pixel in the stencil buffer.
+    fn render_shadows(
+        &mut self,
+        light_pos: vec2<Coord>,
+        camera: &Camera2d,
+        geometry: &[StaticPolygon],
+    ) {
+        // Using `world_texture` here but it is not actually used by the shader
+        let mut light_framebuffer = ugli::Framebuffer::new(
+            self.geng.ugli(),
+            ugli::ColorAttachment::Texture(&mut self.buffers.world_texture),
+            ugli::DepthAttachment::RenderbufferWithStencil(&mut self.buffers.shadow_stencil),
+        );
+        let framebuffer_size = light_framebuffer.size().map(|x| x as f32);
+        ugli::clear(&mut light_framebuffer, None, None, Some(0));
+
+        for polygon in geometry {
+            // Cast shadow
             ugli::draw(
-                &mut world_framebuffer,
-                &self.assets.shaders.spotlight,
+                &mut light_framebuffer,
+                &self.assets.shaders.point_light_shadow_map,
                 ugli::DrawMode::TriangleFan,
-                &self.buffers.quad_geometry,
+                &polygon.doubled,
                 (
                     ugli::uniforms! {
                         u_model_matrix: mat3::identity(),
-                        u_light_pos: spotlight.position.map(Coord::as_f32),
-                        u_light_angle: spotlight.angle,
-                        u_light_angle_range: spotlight.angle_range,
-                        u_light_color: spotlight.color,
-                        u_light_intensity: spotlight.intensity,
-                        u_light_max_distance: spotlight.max_distance.as_f32(),
-                        u_light_volume: spotlight.volume,
-                        u_normal_texture: &self.buffers.normal_texture,
-                        u_source_texture: &self.buffers.world_texture,
-                        u_framebuffer_size: self.buffers.normal_texture.size(),
+                        u_light_pos: light_pos.map(Coord::as_f32),
                     },
                     geng::camera2d_uniforms(camera, framebuffer_size),
                 ),
                 ugli::DrawParameters {
+                    // Just in case the shader writes something in the texture,
+                    // discard it during blending.
                     blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
-                        src_factor: ugli::BlendFactor::One,
+                        src_factor: ugli::BlendFactor::Zero,
                         dst_factor: ugli::BlendFactor::One,
                     })),
-                    // Ignore the parts in shadow
+                    // Increment the shadow casters count
                     stencil_mode: Some(ugli::StencilMode::always(ugli::FaceStencilMode {
                         test: ugli::StencilTest {
-                            condition: ugli::Condition::Equal,
-                            reference: 0, // 0 shadow casters means the point is lit up.
+                            condition: ugli::Condition::Always,
+                            reference: 0,
                             mask: 0xFF,
                         },
-                        op: ugli::StencilOp::always(ugli::StencilOpFunc::Keep),
+                        op: ugli::StencilOp::always(ugli::StencilOpFunc::Increment),
+                    })),
+                    ..Default::default()
+                },
+            );
+            // Remove self-shadow
+            ugli::draw(
+                &mut light_framebuffer,
+                &self.assets.shaders.shadow_remove,
+                ugli::DrawMode::TriangleFan,
+                &polygon.vertices,
+                (
+                    ugli::uniforms! {
+                        u_model_matrix: mat3::identity(),
+                        u_color: Rgba::TRANSPARENT_BLACK,
+                    },
+                    geng::camera2d_uniforms(camera, framebuffer_size),
+                ),
+                ugli::DrawParameters {
+                    // Decrement the shadow casters count
+                    stencil_mode: Some(ugli::StencilMode::always(ugli::FaceStencilMode {
+                        test: ugli::StencilTest {
+                            condition: ugli::Condition::Always,
+                            reference: 0,
+                            mask: 0xFF,
+                        },
+                        op: ugli::StencilOp::always(ugli::StencilOpFunc::Decrement),
                     })),
                     ..Default::default()
                 },
@@ -255,7 +325,11 @@ impl LightsRender {
                     geng::camera2d_uniforms(camera, framebuffer_size),
                 ),
                 ugli::DrawParameters {
-                    blend_mode: Some(ugli::BlendMode::default()),
+                    // Only fill in the pixels not covered by the normal maps of the sprites
+                    blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
+                        src_factor: ugli::BlendFactor::OneMinusDstAlpha,
+                        dst_factor: ugli::BlendFactor::One,
+                    })),
                     ..Default::default()
                 },
             );
@@ -263,6 +337,42 @@ impl LightsRender {
     }
 }
 
+/// Number of shadow samples for lights with a non-zero source radius.
+const SOFT_SHADOW_SAMPLES: usize = 8;
+
+/// Offsets of the points on the light emitter to cast the shadows from.
+fn shadow_samples(source_radius: Coord) -> Vec<vec2<Coord>> {
+    if source_radius <= Coord::ZERO {
+        return vec![vec2::ZERO];
+    }
+    (0..SOFT_SHADOW_SAMPLES)
+        .map(|i| {
+            let angle = i as f32 / SOFT_SHADOW_SAMPLES as f32 * f32::PI * 2.0;
+            vec2(angle.cos(), angle.sin()).map(Coord::new) * source_radius
+        })
+        .collect()
+}
+
+/// Additive blending of a light limited to the pixels without shadow casters.
+fn lit_draw_parameters() -> ugli::DrawParameters {
+    ugli::DrawParameters {
+        blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
+            src_factor: ugli::BlendFactor::One,
+            dst_factor: ugli::BlendFactor::One,
+        })),
+        // Ignore the parts in shadow
+        stencil_mode: Some(ugli::StencilMode::always(ugli::FaceStencilMode {
+            test: ugli::StencilTest {
+                condition: ugli::Condition::Equal,
+                reference: 0, // 0 shadow casters means the point is lit up.
+                mask: 0xFF,
+            },
+            op: ugli::StencilOp::always(ugli::StencilOpFunc::Keep),
+        })),
+        ..Default::default()
+    }
+}
+
 impl Buffers {
     pub fn new(geng: &Geng) -> Self {
         Self {
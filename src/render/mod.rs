@@ -1,10 +1,14 @@
 use super::*;
 
 mod lights;
+mod post;
+mod screen;
 mod util;
 mod world;
 
 pub use lights::*;
+pub use post::*;
+pub use screen::*;
 pub use util::*;
 pub use world::*;
 
@@ -32,23 +36,33 @@ impl GameRender {
         framebuffer: &mut ugli::Framebuffer,
     ) {
         // Draw the world and normals ignoring lighting
-        let (mut world_framebuffer, _normal_framebuffer) = self.lights.start_render(framebuffer);
+        let (mut world_framebuffer, mut normal_framebuffer) = self.lights.start_render(framebuffer);
 
         // Render world
         self.world.draw_world(
             world,
             draw_hitboxes,
             &mut world_framebuffer,
-            None,
-            // Some(&mut normal_framebuffer),
+            Some(&mut normal_framebuffer),
         );
 
         self.lights.finish_render(
-            &world.level,
+            &world.scene_lights(),
+            world.level.grade.as_deref(),
             &world.light_geometry,
             &world.camera,
             framebuffer,
         );
+
+        // Flash
+        if world.juice.flash.a > 0.0 {
+            let screen = Aabb2::ZERO.extend_positive(framebuffer.size().map(|x| x as f32));
+            self.geng.draw_2d(
+                framebuffer,
+                &geng::PixelPerfectCamera,
+                &draw_2d::Quad::new(screen, world.juice.flash),
+            );
+        }
     }
 
     pub fn draw_ui(
@@ -117,6 +131,16 @@ impl GameRender {
                 .align_bounding_box(vec2(0.0, 1.0))
                 .translate(pos - vec2(0.0, size * 2.5)),
             );
+            if world.assists.used {
+                self.geng.draw_2d(
+                    framebuffer,
+                    &geng::PixelPerfectCamera,
+                    &draw_2d::Text::unit(&*self.assets.font, "assist", Rgba::GRAY)
+                        .scale_uniform(size * 0.7)
+                        .align_bounding_box(vec2(0.0, 1.0))
+                        .translate(pos - vec2(0.0, size * 4.5)),
+                );
+            }
         }
     }
 }
@@ -0,0 +1,225 @@
+use super::*;
+
+/// Configuration of the post-processing effects.
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct PostConfig {
+    pub bloom: BloomConfig,
+    pub vignette: VignetteConfig,
+    pub crt: CrtConfig,
+    /// Color grade used by the levels that do not choose their own.
+    pub grade: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloomConfig {
+    pub enabled: bool,
+    /// Brightness above which the pixels start to glow.
+    pub threshold: f32,
+    pub intensity: f32,
+    /// Number of blur passes, more passes make a wider glow.
+    pub passes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VignetteConfig {
+    /// How much the corners are darkened, from 0 to 1.
+    pub strength: f32,
+    /// Distance from the center relative to the screen where the darkening starts.
+    pub radius: f32,
+}
+
+/// Scanline filter applied when the screen is scaled up to the window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrtConfig {
+    pub enabled: bool,
+    /// How dark the gaps between the pixel rows are, from 0 to 1.
+    pub scanlines: f32,
+    /// Bulge of the screen.
+    pub curvature: f32,
+}
+
+/// Color grading lookup tables by name.
+pub struct ColorGrades {
+    /// Names in the order of `grades.json`.
+    names: Vec<String>,
+    luts: HashMap<String, ugli::Texture>,
+    /// Lookup table that keeps the colors, used in place of the missing grades.
+    identity: ugli::Texture,
+}
+
+/// Applies the post-processing effects to the lit world.
+pub struct PostRender {
+    geng: Geng,
+    assets: Rc<Assets>,
+    /// Constant unit quad geometry.
+    quad_geometry: ugli::VertexBuffer<draw_2d::Vertex>,
+    /// Half resolution textures the bloom is blurred between.
+    bloom_textures: [ugli::Texture; 2],
+    /// Grades that were not found and have already been reported.
+    missing_grades: HashSet<String>,
+}
+
+impl ColorGrades {
+    pub fn get(&self, name: &str) -> Option<&ugli::Texture> {
+        self.luts.get(name)
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn identity(&self) -> &ugli::Texture {
+        &self.identity
+    }
+}
+
+impl PostRender {
+    pub fn new(geng: &Geng, assets: &Rc<Assets>) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            quad_geometry: ugli::VertexBuffer::new_static(
+                geng.ugli(),
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                    .into_iter()
+                    .map(|(x, y)| draw_2d::Vertex { a_pos: vec2(x, y) })
+                    .collect(),
+            ),
+            bloom_textures: [new_texture(geng), new_texture(geng)],
+            missing_grades: HashSet::new(),
+        }
+    }
+
+    /// Draws the `source` texture onto the framebuffer with the post effects applied.
+    /// Uses the default grade if `grade` is `None` or unknown,
+    /// and no grading if the default one is unknown too.
+    pub fn apply(
+        &mut self,
+        source: &ugli::Texture,
+        grade: Option<&str>,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let bloom_intensity = if self.assets.post.bloom.enabled {
+            self.render_bloom(source);
+            self.assets.post.bloom.intensity
+        } else {
+            0.0
+        };
+
+        let config = &self.assets.post;
+
+        let grades = &self.assets.grades;
+        let mut find = |name: &str| {
+            let lut = grades.get(name);
+            if lut.is_none() && self.missing_grades.insert(name.to_owned()) {
+                warn!("Unknown color grade {name:?}");
+            }
+            lut
+        };
+        let lut = grade
+            .and_then(&mut find)
+            .or_else(|| find(&config.grade))
+            .unwrap_or_else(|| grades.identity());
+
+        ugli::draw(
+            framebuffer,
+            &self.assets.shaders.post,
+            ugli::DrawMode::TriangleFan,
+            &self.quad_geometry,
+            ugli::uniforms! {
+                u_source_texture: source,
+                u_bloom_texture: &self.bloom_textures[0],
+                u_bloom_intensity: bloom_intensity,
+                u_lut: lut,
+                u_vignette_strength: config.vignette.strength,
+                u_vignette_radius: config.vignette.radius,
+            },
+            ugli::DrawParameters::default(),
+        );
+    }
+
+    /// Extracts the bright parts of the source and blurs them into the first bloom texture.
+    fn render_bloom(&mut self, source: &ugli::Texture) {
+        let config = &self.assets.post.bloom;
+        let size = source.size().map(|x| (x / 2).max(1));
+        for texture in &mut self.bloom_textures {
+            if texture.size() != size {
+                *texture = ugli::Texture::new_with(self.geng.ugli(), size, |_| Rgba::BLACK);
+            }
+        }
+
+        let [bright, blurred] = &mut self.bloom_textures;
+        ugli::draw(
+            &mut attach_texture(bright, &self.geng),
+            &self.assets.shaders.post_bright,
+            ugli::DrawMode::TriangleFan,
+            &self.quad_geometry,
+            ugli::uniforms! {
+                u_source_texture: source,
+                u_threshold: config.threshold,
+            },
+            ugli::DrawParameters::default(),
+        );
+
+        // Separable gaussian blur ending up in the first texture
+        let blur = |from: &ugli::Texture, to: &mut ugli::Texture, direction: vec2<f32>| {
+            ugli::draw(
+                &mut attach_texture(to, &self.geng),
+                &self.assets.shaders.post_blur,
+                ugli::DrawMode::TriangleFan,
+                &self.quad_geometry,
+                ugli::uniforms! {
+                    u_source_texture: from,
+                    u_texture_size: size,
+                    u_direction: direction,
+                },
+                ugli::DrawParameters::default(),
+            );
+        };
+        for _ in 0..config.passes {
+            blur(bright, blurred, vec2(1.0, 0.0));
+            blur(blurred, bright, vec2(0.0, 1.0));
+        }
+    }
+}
+
+impl geng::LoadAsset for ColorGrades {
+    fn load(geng: &Geng, path: &std::path::Path) -> geng::AssetFuture<Self> {
+        let geng = geng.clone();
+        let path = path.to_owned();
+        async move {
+            let list = <String as geng::LoadAsset>::load(&geng, &path.join("grades.json")).await?;
+            let names: Vec<String> = serde_json::from_str(&list)?;
+            let mut luts = HashMap::new();
+            for name in &names {
+                let lut = ugli::Texture::load(&geng, &path.join(format!("{name}.png"))).await?;
+                luts.insert(name.clone(), lut);
+            }
+            Ok(Self {
+                names,
+                luts,
+                identity: identity_lut(&geng),
+            })
+        }
+        .boxed_local()
+    }
+
+    const DEFAULT_EXT: Option<&'static str> = None;
+}
+
+/// Number of cells along each axis of the lookup tables, see `post.glsl`.
+const LUT_SIZE: usize = 16;
+
+/// Lookup table that maps every color to itself.
+fn identity_lut(geng: &Geng) -> ugli::Texture {
+    let max = (LUT_SIZE - 1) as f32;
+    ugli::Texture::new_with(geng.ugli(), vec2(LUT_SIZE * LUT_SIZE, LUT_SIZE), |pos| {
+        Rgba::new(
+            (pos.x % LUT_SIZE) as f32 / max,
+            pos.y as f32 / max,
+            (pos.x / LUT_SIZE) as f32 / max,
+            1.0,
+        )
+    })
+}
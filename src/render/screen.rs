@@ -0,0 +1,156 @@
+use super::*;
+
+/// Configuration of the internal pixel resolution.
+#[derive(Debug, Clone, Serialize, Deserialize, geng::Assets)]
+#[asset(json)]
+pub struct ScreenConfig {
+    /// Minimum number of pixels along the shorter side of the screen.
+    pub pixels: usize,
+    /// Scale the pixels only by whole numbers.
+    pub integer_scaling: bool,
+    /// Narrowest supported aspect ratio (width / height), narrower windows get letterboxed.
+    pub min_aspect: f32,
+    /// Widest supported aspect ratio, wider windows get letterboxed.
+    pub max_aspect: f32,
+}
+
+/// Low resolution texture the game is rendered onto before being scaled up to the window.
+pub struct PixelScreen {
+    geng: Geng,
+    config: ScreenConfig,
+    texture: ugli::Texture,
+    /// Where the texture is drawn on the window.
+    target: Aabb2<f32>,
+}
+
+impl ScreenConfig {
+    /// Calculates the internal resolution for the window
+    /// and the area of the window it is drawn to.
+    pub fn fit(&self, framebuffer_size: vec2<usize>) -> (vec2<usize>, Aabb2<f32>) {
+        let framebuffer_size = framebuffer_size.map(|x| (x as f32).max(1.0));
+        let aspect = framebuffer_size.x / framebuffer_size.y;
+        let area = if aspect > self.max_aspect {
+            vec2(framebuffer_size.y * self.max_aspect, framebuffer_size.y)
+        } else if aspect < self.min_aspect {
+            vec2(framebuffer_size.x, framebuffer_size.x / self.min_aspect)
+        } else {
+            framebuffer_size
+        };
+
+        let scale = area.x.min(area.y) / self.pixels as f32;
+        let scale = if self.integer_scaling {
+            scale.floor().max(1.0)
+        } else {
+            scale
+        };
+        let resolution = area.map(|x| ((x / scale).floor() as usize).max(1));
+        let target = Aabb2::point(framebuffer_size / 2.0)
+            .extend_symmetric(resolution.map(|x| x as f32) * scale / 2.0);
+        (resolution, target)
+    }
+
+    /// Fits a fixed resolution image into the window.
+    pub fn fit_fixed(&self, resolution: vec2<usize>, framebuffer_size: vec2<usize>) -> Aabb2<f32> {
+        let framebuffer_size = framebuffer_size.map(|x| x as f32);
+        let size = resolution.map(|x| x as f32);
+        let scale = framebuffer_size / size;
+        let scale = scale.x.min(scale.y);
+        let scale = if self.integer_scaling && scale >= 1.0 {
+            scale.floor()
+        } else {
+            scale
+        };
+        Aabb2::point(framebuffer_size / 2.0).extend_symmetric(size * scale / 2.0)
+    }
+}
+
+impl PixelScreen {
+    pub fn new(geng: &Geng, config: &ScreenConfig) -> Self {
+        let (resolution, target) = config.fit(geng.window().size());
+        Self {
+            geng: geng.clone(),
+            config: config.clone(),
+            texture: {
+                let mut texture = ugli::Texture::new_with(geng.ugli(), resolution, |_| Rgba::BLACK);
+                texture.set_filter(ugli::Filter::Nearest);
+                texture
+            },
+            target,
+        }
+    }
+
+    /// Resizes the texture to fit the window.
+    pub fn update(&mut self, framebuffer_size: vec2<usize>) {
+        let (resolution, target) = self.config.fit(framebuffer_size);
+        self.target = target;
+        if self.texture.size() != resolution {
+            self.texture = ugli::Texture::new_with(self.geng.ugli(), resolution, |_| Rgba::BLACK);
+            self.texture.set_filter(ugli::Filter::Nearest);
+        }
+    }
+
+    pub fn resolution(&self) -> vec2<usize> {
+        self.texture.size()
+    }
+
+    /// The area of the window the screen is drawn to.
+    pub fn target(&self) -> Aabb2<f32> {
+        self.target
+    }
+
+    /// A camera that matches the `camera` used for the pixel screen,
+    /// but draws straight onto the window.
+    pub fn window_camera(&self, camera: &Camera2d, framebuffer_size: vec2<usize>) -> Camera2d {
+        Camera2d {
+            fov: camera.fov * framebuffer_size.y as f32 / self.target.height(),
+            ..camera.clone()
+        }
+    }
+
+    pub fn framebuffer(&mut self) -> ugli::Framebuffer {
+        attach_texture(&mut self.texture, &self.geng)
+    }
+
+    /// Draws the screen scaled up onto the window.
+    pub fn draw(&self, framebuffer: &mut ugli::Framebuffer) {
+        self.geng.draw_2d(
+            framebuffer,
+            &geng::PixelPerfectCamera,
+            &draw_2d::TexturedQuad::new(self.target, &self.texture),
+        );
+    }
+
+    /// Draws the screen scaled up onto the window through the CRT filter.
+    pub fn draw_crt(&self, framebuffer: &mut ugli::Framebuffer, assets: &Assets) {
+        let config = &assets.post.crt;
+        let geometry = ugli::VertexBuffer::new_dynamic(
+            self.geng.ugli(),
+            self.target
+                .corners()
+                .into_iter()
+                .zip([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)])
+                .map(|(a_pos, (u, v))| Vertex {
+                    a_pos,
+                    a_uv: vec2(u, v),
+                })
+                .collect(),
+        );
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        ugli::draw(
+            framebuffer,
+            &assets.shaders.crt,
+            ugli::DrawMode::TriangleFan,
+            &geometry,
+            (
+                ugli::uniforms! {
+                    u_texture: &self.texture,
+                    u_texture_size: self.texture.size(),
+                    u_scanlines: config.scanlines,
+                    u_curvature: config.curvature,
+                },
+                geng::camera2d_uniforms(&geng::PixelPerfectCamera, framebuffer_size),
+            ),
+            ugli::DrawParameters::default(),
+        );
+    }
+}
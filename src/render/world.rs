@@ -3,6 +3,28 @@ use super::*;
 pub struct WorldRender {
     geng: Geng,
     assets: Rc<Assets>,
+    quad_geometry: ugli::VertexBuffer<draw_2d::Vertex>,
+}
+
+#[derive(ugli::Vertex, Debug, Clone, Copy)]
+struct ParticleInstance {
+    i_pos: vec2<f32>,
+    i_radius: f32,
+    i_rotation: f32,
+    i_color: Rgba<f32>,
+    /// 0 for circles, 1 for squares.
+    i_shape: f32,
+}
+
+/// A frame of the player animation placed in the world.
+struct PlayerSprite<'a> {
+    clip: &'a Animation,
+    frame: usize,
+    /// Quad in the sprite's local coordinates, mirrored when the player is flipped.
+    aabb: Aabb2<f32>,
+    transform: mat3<f32>,
+    /// Rotation and flip of the sprite to apply to its normals.
+    normal_matrix: mat3<f32>,
 }
 
 impl WorldRender {
@@ -10,6 +32,13 @@ impl WorldRender {
         Self {
             geng: geng.clone(),
             assets: assets.clone(),
+            quad_geometry: ugli::VertexBuffer::new_static(
+                geng.ugli(),
+                [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                    .into_iter()
+                    .map(|(x, y)| draw_2d::Vertex { a_pos: vec2(x, y) })
+                    .collect(),
+            ),
         }
     }
 
@@ -18,20 +47,30 @@ impl WorldRender {
         world: &World,
         draw_hitboxes: bool,
         framebuffer: &mut ugli::Framebuffer,
-        _normal_framebuffer: Option<&mut ugli::Framebuffer>,
+        normal_framebuffer: Option<&mut ugli::Framebuffer>,
     ) {
-        // TODO: normals
         self.draw_background(world, framebuffer);
         self.draw_level(
             &world.level,
             &world.geometry.0,
             &world.geometry.1,
+            world.time,
+            draw_hitboxes,
+            &world.camera,
+            framebuffer,
+        );
+        self.draw_player(
+            &world.player,
+            world.juice.squash,
             draw_hitboxes,
             &world.camera,
             framebuffer,
         );
-        self.draw_player(&world.player, draw_hitboxes, &world.camera, framebuffer);
         self.draw_particles(&world.particles, &world.camera, framebuffer);
+
+        if let Some(normal_framebuffer) = normal_framebuffer {
+            self.draw_world_normals(world, normal_framebuffer);
+        }
     }
 
     pub fn draw_background(&self, world: &World, framebuffer: &mut ugli::Framebuffer) {
@@ -98,6 +137,7 @@ impl WorldRender {
                     ugli::uniforms! {
                         u_model_matrix: matrix,
                         u_texture: texture,
+                        u_uv_offset: vec2::<f32>::ZERO,
                     },
                     geng::camera2d_uniforms(&world.camera, framebuffer.size().map(|x| x as f32)),
                 ),
@@ -129,6 +169,7 @@ impl WorldRender {
                 ugli::uniforms! {
                     u_model_matrix: matrix,
                     u_texture: texture,
+                    u_uv_offset: vec2::<f32>::ZERO,
                 },
                 geng::camera2d_uniforms(&world.camera, framebuffer.size().map(|x| x as f32)),
             ),
@@ -142,14 +183,15 @@ impl WorldRender {
     pub fn draw_level(
         &self,
         level: &Level,
-        tiles_geometry: &HashMap<Tile, ugli::VertexBuffer<Vertex>>,
-        masked_geometry: &HashMap<Tile, ugli::VertexBuffer<MaskedVertex>>,
+        tiles_geometry: &HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        masked_geometry: &HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
+        time: Time,
         draw_hitboxes: bool,
         camera: &impl geng::AbstractCamera2d,
         framebuffer: &mut ugli::Framebuffer,
     ) {
         self.draw_props(&level.props, camera, framebuffer);
-        self.draw_tiles(tiles_geometry, masked_geometry, camera, framebuffer);
+        self.draw_tiles(tiles_geometry, masked_geometry, time, camera, framebuffer);
         self.draw_hazards(&level.hazards, draw_hitboxes, camera, framebuffer);
         self.draw_coins(&level.coins, draw_hitboxes, camera, framebuffer);
 
@@ -177,8 +219,9 @@ impl WorldRender {
     pub fn draw_level_editor(
         &self,
         level: &Level,
-        tiles_geometry: &HashMap<Tile, ugli::VertexBuffer<Vertex>>,
-        masked_geometry: &HashMap<Tile, ugli::VertexBuffer<MaskedVertex>>,
+        tiles_geometry: &HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        masked_geometry: &HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
+        time: Time,
         draw_hitboxes: bool,
         camera: &impl geng::AbstractCamera2d,
         framebuffer: &mut ugli::Framebuffer,
@@ -187,6 +230,7 @@ impl WorldRender {
             level,
             tiles_geometry,
             masked_geometry,
+            time,
             draw_hitboxes,
             camera,
             framebuffer,
@@ -216,18 +260,31 @@ impl WorldRender {
                 &draw_2d::TexturedQuad::new(aabb, &self.assets.sprites.spotlight),
             );
         }
+
+        // Point lights
+        for light in &level.point_lights {
+            let pos = pixel_perfect_pos(light.position);
+            let size = vec2(1.0, 1.0);
+            let aabb = Aabb2::point(pos).extend_symmetric(size / 2.0);
+            self.geng.draw_2d(
+                framebuffer,
+                camera,
+                &draw_2d::TexturedQuad::new(aabb, &self.assets.sprites.point_light),
+            );
+        }
     }
 
     pub fn draw_tiles(
         &self,
-        tiles_geometry: &HashMap<Tile, ugli::VertexBuffer<Vertex>>,
-        masked_geometry: &HashMap<Tile, ugli::VertexBuffer<MaskedVertex>>,
+        tiles_geometry: &HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        masked_geometry: &HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
+        time: Time,
         camera: &impl geng::AbstractCamera2d,
         framebuffer: &mut ugli::Framebuffer,
     ) {
         let mask = self.assets.sprites.tiles.mask.texture();
-        for (tile, geometry) in masked_geometry {
-            let set = self.assets.sprites.tiles.get_tile_set(tile);
+        for (group, geometry) in masked_geometry {
+            let set = self.assets.sprites.tiles.get_tile_set(&group.tile);
             let texture = set.texture();
             ugli::draw(
                 framebuffer,
@@ -239,6 +296,7 @@ impl WorldRender {
                         u_model_matrix: mat3::identity(),
                         u_texture: texture,
                         u_mask: mask,
+                        u_uv_offset: set.config.animation_offset(group.animation, time),
                     },
                     geng::camera2d_uniforms(camera, framebuffer.size().map(|x| x as f32)),
                 ),
@@ -248,8 +306,8 @@ impl WorldRender {
                 },
             );
         }
-        for (tile, geometry) in tiles_geometry {
-            let set = self.assets.sprites.tiles.get_tile_set(tile);
+        for (group, geometry) in tiles_geometry {
+            let set = self.assets.sprites.tiles.get_tile_set(&group.tile);
             let texture = set.texture();
             ugli::draw(
                 framebuffer,
@@ -260,6 +318,7 @@ impl WorldRender {
                     ugli::uniforms! {
                         u_model_matrix: mat3::identity(),
                         u_texture: texture,
+                        u_uv_offset: set.config.animation_offset(group.animation, time),
                     },
                     geng::camera2d_uniforms(camera, framebuffer.size().map(|x| x as f32)),
                 ),
@@ -355,95 +414,257 @@ impl WorldRender {
     pub fn draw_player(
         &self,
         player: &Player,
+        squash: f32,
         draw_hitboxes: bool,
         camera: &impl geng::AbstractCamera2d,
         framebuffer: &mut ugli::Framebuffer,
     ) {
-        if let PlayerState::Respawning { .. } = player.state {
-        } else {
-            let sprites = &self.assets.sprites.player;
-            let mut flip = player.facing_left;
-            let (texture, transform) = match player.state {
-                PlayerState::Drilling | PlayerState::AirDrill { .. } => {
-                    let mut velocity = player.velocity.map(|x| {
-                        if x.as_f32().abs() < 1.0 {
-                            0.00
-                        } else {
-                            x.as_f32()
-                        }
-                    });
-                    if velocity == vec2::ZERO {
-                        velocity.y = 1.0;
-                    }
-                    flip = false;
-                    let mut angle = (velocity.arg() / f32::PI * 4.0 + 2.0).round();
-                    let drill = if angle as i32 % 2 == 0 {
-                        // Vertical/horizontal
-                        &sprites.drill.drill_v0
-                    } else {
-                        // Diagonal
-                        angle -= 1.0;
-                        &sprites.drill.drill_d0
-                    };
-                    (drill, mat3::rotate(angle * f32::PI / 4.0))
-                }
-                PlayerState::WallSliding { wall_normal, .. } => {
-                    flip = wall_normal.x < Coord::ZERO;
-                    (&sprites.slide0, mat3::identity())
-                }
-                _ => (&sprites.idle0, mat3::identity()),
-            };
-
-            let pos = player.collider.feet();
-            let size = texture.size().map(|x| x as f32) / PIXELS_PER_UNIT;
-            let pos = pixel_perfect_pos(pos) + vec2(0.0, size.y / 2.0);
-            let transform = mat3::translate(pos) * transform;
+        if let Some(sprite) = self.player_sprite(player, squash) {
             self.geng.draw_2d_transformed(
                 framebuffer,
                 camera,
-                &draw_2d::TexturedQuad::new(
-                    Aabb2::ZERO
-                        .extend_symmetric(size / 2.0 * vec2(if flip { -1.0 } else { 1.0 }, 1.0)),
-                    texture,
+                &draw_2d::TexturedQuad::new(sprite.aabb, &sprite.clip.frames[sprite.frame].0),
+                sprite.transform,
+            );
+        }
+
+        if draw_hitboxes && !matches!(player.state, PlayerState::Respawning { .. }) {
+            self.geng.draw_2d(
+                framebuffer,
+                camera,
+                &draw_2d::Quad::new(
+                    player.collider.raw().map(Coord::as_f32),
+                    Rgba::new(0.0, 1.0, 0.0, 0.7),
                 ),
-                transform,
             );
-            if draw_hitboxes {
-                self.geng.draw_2d(
+        }
+    }
+
+    /// Calculates which frame of the player animation to draw and where.
+    fn player_sprite(&self, player: &Player, squash: f32) -> Option<PlayerSprite<'_>> {
+        let sprites = &self.assets.sprites.player;
+        let animation = player.animation;
+        let mut flip = player.facing_left;
+        let (clip, rotation) = match animation.state {
+            PlayerAnimationState::Drill => {
+                let mut velocity = player.velocity.map(|x| {
+                    if x.as_f32().abs() < 1.0 {
+                        0.00
+                    } else {
+                        x.as_f32()
+                    }
+                });
+                if velocity == vec2::ZERO {
+                    velocity.y = 1.0;
+                }
+                flip = false;
+                let mut angle = (velocity.arg() / f32::PI * 4.0 + 2.0).round();
+                let drill = if angle as i32 % 2 == 0 {
+                    // Vertical/horizontal
+                    &sprites.drill.drill_v
+                } else {
+                    // Diagonal
+                    angle -= 1.0;
+                    &sprites.drill.drill_d
+                };
+                (drill, mat3::rotate(angle * f32::PI / 4.0))
+            }
+            PlayerAnimationState::WallSlide => {
+                if let PlayerState::WallSliding { wall_normal, .. } = player.state {
+                    flip = wall_normal.x < Coord::ZERO;
+                }
+                (&sprites.wall_slide, mat3::identity())
+            }
+            state => (sprites.get_animation(state), mat3::identity()),
+        };
+        let frame = match animation.state {
+            // The player disappears after the death animation
+            PlayerAnimationState::Death => clip.frame_index_once(animation.time),
+            _ => clip.frame_index(animation.time),
+        }?;
+
+        let pos = player.collider.feet();
+        let size = clip.frames[frame].0.size().map(|x| x as f32) / PIXELS_PER_UNIT;
+        // Squash and stretch around the feet
+        let stretch = vec2(1.0 - squash, 1.0 + squash);
+        let transform = mat3::translate(pixel_perfect_pos(pos))
+            * mat3::scale(stretch)
+            * mat3::translate(vec2(0.0, size.y / 2.0))
+            * rotation;
+        let flip = vec2(if flip { -1.0 } else { 1.0 }, 1.0);
+        Some(PlayerSprite {
+            clip,
+            frame,
+            aabb: Aabb2::ZERO.extend_symmetric(size / 2.0 * flip),
+            transform,
+            normal_matrix: rotation * mat3::scale(flip),
+        })
+    }
+
+    /// Draws the normal maps of the level and the player into the normal buffer.
+    pub fn draw_world_normals(&self, world: &World, framebuffer: &mut ugli::Framebuffer) {
+        self.draw_level_normals(
+            &world.level,
+            &world.geometry.0,
+            &world.geometry.1,
+            world.time,
+            &world.camera,
+            framebuffer,
+        );
+        if let Some(sprite) = self.player_sprite(&world.player, world.juice.squash) {
+            if let Some(normal) = sprite.clip.get_normal(sprite.frame) {
+                self.draw_normal_quad(
+                    sprite.aabb,
+                    sprite.transform,
+                    sprite.normal_matrix,
+                    normal,
+                    &world.camera,
                     framebuffer,
-                    camera,
-                    &draw_2d::Quad::new(
-                        player.collider.raw().map(Coord::as_f32),
-                        Rgba::new(0.0, 1.0, 0.0, 0.7),
-                    ),
                 );
             }
         }
     }
 
+    pub fn draw_level_normals(
+        &self,
+        level: &Level,
+        tiles_geometry: &HashMap<TileGroup, ugli::VertexBuffer<Vertex>>,
+        masked_geometry: &HashMap<TileGroup, ugli::VertexBuffer<MaskedVertex>>,
+        time: Time,
+        camera: &impl geng::AbstractCamera2d,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        for prop in &level.props {
+            let normal = self.assets.sprites.props.get_normal(&prop.prop_type);
+            self.draw_normal_quad(
+                prop.sprite.map(Coord::as_f32),
+                mat3::identity(),
+                mat3::identity(),
+                normal,
+                camera,
+                framebuffer,
+            );
+        }
+
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        let mask = self.assets.sprites.tiles.mask.texture();
+        for (group, geometry) in masked_geometry {
+            let set = self.assets.sprites.tiles.get_tile_set(&group.tile);
+            let Some(normal) = set.normal_texture() else {
+                continue;
+            };
+            ugli::draw(
+                framebuffer,
+                &self.assets.shaders.normal_texture_mask,
+                ugli::DrawMode::Triangles,
+                geometry,
+                (
+                    ugli::uniforms! {
+                        u_model_matrix: mat3::identity(),
+                        u_texture: normal,
+                        u_mask: mask,
+                        u_normal_influence: 1.0,
+                        u_uv_offset: set.config.animation_offset(group.animation, time),
+                    },
+                    geng::camera2d_uniforms(camera, framebuffer_size),
+                ),
+                ugli::DrawParameters::default(),
+            );
+        }
+        for (group, geometry) in tiles_geometry {
+            let set = self.assets.sprites.tiles.get_tile_set(&group.tile);
+            let Some(normal) = set.normal_texture() else {
+                continue;
+            };
+            ugli::draw(
+                framebuffer,
+                &self.assets.shaders.normal_texture,
+                ugli::DrawMode::Triangles,
+                geometry,
+                (
+                    ugli::uniforms! {
+                        u_model_matrix: mat3::identity(),
+                        u_texture: normal,
+                        u_normal_matrix: mat3::identity(),
+                        u_normal_influence: 1.0,
+                        u_uv_offset: set.config.animation_offset(group.animation, time),
+                    },
+                    geng::camera2d_uniforms(camera, framebuffer_size),
+                ),
+                ugli::DrawParameters::default(),
+            );
+        }
+    }
+
+    /// Draws a normal map over the quad.
+    /// The `normal_matrix` rotates and flips the normals along with the sprite.
+    fn draw_normal_quad(
+        &self,
+        aabb: Aabb2<f32>,
+        transform: mat3<f32>,
+        normal_matrix: mat3<f32>,
+        normal: &ugli::Texture,
+        camera: &impl geng::AbstractCamera2d,
+        framebuffer: &mut ugli::Framebuffer,
+    ) {
+        let corners = aabb.corners();
+        let uvs = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let vertices = [0, 1, 2, 3].map(|i| Vertex {
+            a_pos: corners[i],
+            a_uv: vec2(uvs[i].0, uvs[i].1),
+        });
+        let geometry = ugli::VertexBuffer::new_dynamic(self.geng.ugli(), vertices.to_vec());
+        ugli::draw(
+            framebuffer,
+            &self.assets.shaders.normal_texture,
+            ugli::DrawMode::TriangleFan,
+            &geometry,
+            (
+                ugli::uniforms! {
+                    u_model_matrix: transform,
+                    u_texture: normal,
+                    u_normal_matrix: normal_matrix,
+                    u_normal_influence: 1.0,
+                    u_uv_offset: vec2::<f32>::ZERO,
+                },
+                geng::camera2d_uniforms(camera, framebuffer.size().map(|x| x as f32)),
+            ),
+            ugli::DrawParameters::default(),
+        );
+    }
+
     pub fn draw_particles(
         &self,
         particles: &[Particle],
         camera: &impl geng::AbstractCamera2d,
         framebuffer: &mut ugli::Framebuffer,
     ) {
+        let mut instances = Vec::new();
         for particle in particles {
             let texture = match particle.particle_type {
                 ParticleType::Heart4 => &self.assets.sprites.heart4,
                 ParticleType::Heart8 => &self.assets.sprites.heart8,
-                ParticleType::Circle { radius, color } => {
-                    let t =
+                ParticleType::Shape {
+                    shape,
+                    radius,
+                    mut color,
+                } => {
+                    let t = particle.lifetime / particle.initial_lifetime;
+                    let shrink =
                         particle.lifetime.min(Time::ONE) / particle.initial_lifetime.min(Time::ONE);
-                    let radius = (radius * t).as_f32();
-                    self.geng.draw_2d(
-                        framebuffer,
-                        camera,
-                        &draw_2d::Ellipse::circle(
-                            particle.position.map(Coord::as_f32),
-                            radius,
-                            color,
-                        ),
-                    );
+                    if particle.fade {
+                        color.a *= t.as_f32();
+                    }
+                    instances.push(ParticleInstance {
+                        i_pos: particle.position.map(Coord::as_f32),
+                        i_radius: (radius * shrink).as_f32(),
+                        i_rotation: particle.rotation,
+                        i_color: color,
+                        i_shape: match shape {
+                            ParticleShape::Circle => 0.0,
+                            ParticleShape::Square => 1.0,
+                        },
+                    });
                     continue;
                 }
             };
@@ -457,5 +678,23 @@ impl WorldRender {
                 ),
             );
         }
+
+        if instances.is_empty() {
+            return;
+        }
+        // Draw all shapes in one call
+        let instances = ugli::VertexBuffer::new_dynamic(self.geng.ugli(), instances);
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        ugli::draw(
+            framebuffer,
+            &self.assets.shaders.particle,
+            ugli::DrawMode::TriangleFan,
+            ugli::instanced(&self.quad_geometry, &instances),
+            geng::camera2d_uniforms(camera, framebuffer_size),
+            ugli::DrawParameters {
+                blend_mode: Some(ugli::BlendMode::default()),
+                ..Default::default()
+            },
+        );
     }
 }
@@ -0,0 +1,309 @@
+use super::*;
+
+use image::RgbaImage;
+
+/// Renders levels into images on the CPU, without a graphics context.
+/// Used to generate level select thumbnails and previews on headless machines.
+pub struct ThumbnailRender {
+    mask: CpuTileSet,
+    air: CpuTileSet,
+    grass: CpuTileSet,
+    stone: CpuTileSet,
+    spikes: RgbaImage,
+    tutorial_drill_use: RgbaImage,
+    tutorial_drill_jump: RgbaImage,
+    coin: RgbaImage,
+    partner: RgbaImage,
+    /// First frame of the idle animation.
+    player: RgbaImage,
+}
+
+/// A tileset image together with its autotiling rules.
+struct CpuTileSet {
+    image: RgbaImage,
+    config: TileSetConfig,
+}
+
+/// Image the level is drawn onto.
+struct Canvas {
+    image: RgbaImage,
+    /// Area of the world covered by the image.
+    bounds: Aabb2<f32>,
+    pixels_per_unit: f32,
+}
+
+/// A textured quad drawn onto the [Canvas].
+struct Quad<'a> {
+    texture: &'a RgbaImage,
+    /// Part of the texture to draw, with the origin at the bottom left like in ugli.
+    uv: Aabb2<f32>,
+    /// Multiplies the color by the mask texture sampled at the same position.
+    mask: Option<(&'a RgbaImage, Aabb2<f32>)>,
+    target: Aabb2<f32>,
+    /// Rotation around the center of the target.
+    rotation: f32,
+    /// Mirrors the texture horizontally.
+    flip: bool,
+}
+
+impl ThumbnailRender {
+    /// Loads the sprites from the assets directory.
+    pub fn load(assets_dir: &std::path::Path) -> anyhow::Result<Self> {
+        let sprites = assets_dir.join("sprites");
+        let load = |path: std::path::PathBuf| -> anyhow::Result<RgbaImage> {
+            let image = image::open(&path)
+                .map_err(|err| anyhow::anyhow!("Failed to load {path:?}: {err}"))?;
+            Ok(image.into_rgba8())
+        };
+        let tiles = sprites.join("tiles");
+        let tile_set = |name: &str| -> anyhow::Result<CpuTileSet> {
+            let image = load(tiles.join(format!("{name}.png")))?;
+            let file = std::fs::File::open(tiles.join(format!("{name}_config.json")))?;
+            let config = serde_json::from_reader(std::io::BufReader::new(file))?;
+            Ok(CpuTileSet { image, config })
+        };
+
+        let idle = load(sprites.join("player").join("idle.png"))?;
+        let size = idle.height();
+        let player = image::imageops::crop_imm(&idle, 0, 0, size, size).to_image();
+
+        Ok(Self {
+            mask: tile_set("mask")?,
+            air: tile_set("air")?,
+            grass: tile_set("grass")?,
+            stone: tile_set("stone")?,
+            spikes: load(sprites.join("hazards").join("spikes.png"))?,
+            tutorial_drill_use: load(sprites.join("props").join("tutorial_drill_use.png"))?,
+            tutorial_drill_jump: load(sprites.join("props").join("tutorial_drill_jump.png"))?,
+            coin: load(sprites.join("coin.png"))?,
+            partner: load(sprites.join("partner.png"))?,
+            player,
+        })
+    }
+
+    fn get_tile_set(&self, tile: &Tile) -> &CpuTileSet {
+        match tile {
+            Tile::Air => &self.air,
+            Tile::Grass => &self.grass,
+            Tile::Stone => &self.stone,
+        }
+    }
+
+    fn get_hazard(&self, hazard: &HazardType) -> &RgbaImage {
+        match hazard {
+            HazardType::Spikes => &self.spikes,
+        }
+    }
+
+    fn get_prop(&self, prop: &PropType) -> &RgbaImage {
+        match prop {
+            PropType::DrillUse => &self.tutorial_drill_use,
+            PropType::DrillJump => &self.tutorial_drill_jump,
+        }
+    }
+
+    /// Renders the whole level with the given number of pixels per world unit.
+    pub fn render(&self, level: &Level, pixels_per_unit: f32) -> RgbaImage {
+        let bounds = level.bounds().map(Coord::as_f32);
+        let size = bounds
+            .size()
+            .map(|x| ((x * pixels_per_unit).round() as u32).max(1));
+        let mut canvas = Canvas {
+            image: RgbaImage::new(size.x, size.y),
+            bounds,
+            pixels_per_unit,
+        };
+
+        // Same order as in `WorldRender::draw_level`
+        for prop in &level.props {
+            let texture = self.get_prop(&prop.prop_type);
+            canvas.draw(&Quad::new(texture, prop.sprite.map(Coord::as_f32)));
+        }
+        self.draw_tiles(level, &mut canvas);
+        for hazard in &level.hazards {
+            let texture = self.get_hazard(&hazard.hazard_type);
+            let rotation = hazard
+                .direction
+                .map_or(0.0, |dir| dir.map(Coord::as_f32).arg() - f32::PI / 2.0);
+            canvas.draw(&Quad {
+                rotation,
+                ..Quad::new(texture, hazard.sprite.map(Coord::as_f32))
+            });
+        }
+        for coin in &level.coins {
+            canvas.draw(&Quad::new(
+                &self.coin,
+                coin.collider.raw().map(Coord::as_f32),
+            ));
+        }
+        canvas.draw(&Quad {
+            flip: true,
+            ..Quad::new(&self.partner, level.finish().raw().map(Coord::as_f32))
+        });
+
+        // Spawnpoint
+        let feet = pixel_perfect_pos(level.spawn_point);
+        let size =
+            vec2(self.player.width(), self.player.height()).map(|x| x as f32 / PIXELS_PER_UNIT);
+        let target = Aabb2::point(feet + vec2(0.0, size.y / 2.0)).extend_symmetric(size / 2.0);
+        canvas.draw(&Quad::new(&self.player, target));
+
+        canvas.image
+    }
+
+    /// Autotiles the level the same way as [Level::calculate_geometry].
+    fn draw_tiles(&self, level: &Level, canvas: &mut Canvas) {
+        let tile_target = |i: usize| {
+            let pos = index_to_pos(i, level.size.x);
+            let pos = level.grid.grid_to_world(pos.map(|x| x as isize));
+            Aabb2::point(pos)
+                .extend_positive(level.grid.cell_size)
+                .map(Coord::as_f32)
+        };
+        let uv_rect = |set: &CpuTileSet, i: usize, connections| {
+            let seed = position_hash(index_to_pos(i, level.size.x));
+            let uv = set.config.get_tile_variant(connections, seed);
+            Aabb2::from_corners(uv[0], uv[2])
+        };
+
+        // The grass growing over the neighbouring tiles is drawn below the tiles
+        for (i, tile) in level.tiles.tiles().iter().enumerate() {
+            if let Tile::Air = tile {
+                continue;
+            }
+            let neighbours = level.tiles.get_tile_neighbours(i);
+            if neighbours.contains(&Some(Tile::Grass)) {
+                let connections = level.tiles.get_tile_connections(i);
+                let mask = &self.mask;
+                canvas.draw(&Quad {
+                    uv: uv_rect(&self.grass, i, connections),
+                    mask: Some((&mask.image, uv_rect(mask, i, connections))),
+                    ..Quad::new(&self.grass.image, tile_target(i))
+                });
+            }
+        }
+        for (i, tile) in level.tiles.tiles().iter().enumerate() {
+            if let Tile::Air = tile {
+                continue;
+            }
+            let set = self.get_tile_set(tile);
+            let connections = level.tiles.get_tile_connections(i);
+            canvas.draw(&Quad {
+                uv: uv_rect(set, i, connections),
+                ..Quad::new(&set.image, tile_target(i))
+            });
+        }
+    }
+}
+
+/// Downscales the image to fit into a square of `max_size` pixels.
+pub fn fit_image(image: RgbaImage, max_size: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let scale = max_size as f32 / width.max(height) as f32;
+    if scale >= 1.0 {
+        return image;
+    }
+    let size = vec2(width, height).map(|x| ((x as f32 * scale).round() as u32).max(1));
+    image::imageops::resize(
+        &image,
+        size.x,
+        size.y,
+        image::imageops::FilterType::Triangle,
+    )
+}
+
+impl<'a> Quad<'a> {
+    fn new(texture: &'a RgbaImage, target: Aabb2<f32>) -> Self {
+        Self {
+            texture,
+            uv: Aabb2::ZERO.extend_positive(vec2(1.0, 1.0)),
+            mask: None,
+            target,
+            rotation: 0.0,
+            flip: false,
+        }
+    }
+}
+
+impl Canvas {
+    fn world_to_pixel(&self, pos: vec2<f32>) -> vec2<f32> {
+        vec2(pos.x - self.bounds.min.x, self.bounds.max.y - pos.y) * self.pixels_per_unit
+    }
+
+    fn pixel_to_world(&self, pos: vec2<f32>) -> vec2<f32> {
+        let pos = pos / self.pixels_per_unit;
+        vec2(self.bounds.min.x + pos.x, self.bounds.max.y - pos.y)
+    }
+
+    /// Draws the quad sampling the textures with the nearest filter
+    /// and blending with the default alpha blending.
+    fn draw(&mut self, quad: &Quad) {
+        let center = quad.target.center();
+        let size = quad.target.size();
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return;
+        }
+        let (sin, cos) = quad.rotation.sin_cos();
+
+        // Bounding box of the rotated quad
+        let extent = vec2(
+            (size.x * cos).abs() + (size.y * sin).abs(),
+            (size.x * sin).abs() + (size.y * cos).abs(),
+        );
+        let aabb = Aabb2::point(center).extend_symmetric(extent / 2.0);
+        let top_left = self.world_to_pixel(vec2(aabb.min.x, aabb.max.y));
+        let bottom_right = self.world_to_pixel(vec2(aabb.max.x, aabb.min.y));
+        let (width, height) = self.image.dimensions();
+        let xs = top_left.x.floor().max(0.0) as u32..(bottom_right.x.ceil() as u32).min(width);
+        let ys = top_left.y.floor().max(0.0) as u32..(bottom_right.y.ceil() as u32).min(height);
+
+        for y in ys {
+            for x in xs.clone() {
+                let pos = self.pixel_to_world(vec2(x as f32 + 0.5, y as f32 + 0.5)) - center;
+                // Undo the rotation
+                let local = vec2(pos.x * cos + pos.y * sin, pos.y * cos - pos.x * sin);
+                let mut t = local / size + vec2(0.5, 0.5);
+                if !(0.0..1.0).contains(&t.x) || !(0.0..1.0).contains(&t.y) {
+                    continue;
+                }
+                if quad.flip {
+                    t.x = 1.0 - t.x;
+                }
+
+                let mut color = sample(quad.texture, quad.uv, t);
+                if let Some((mask, uv)) = quad.mask {
+                    let mask = sample(mask, uv, t);
+                    for (c, m) in color.iter_mut().zip(mask) {
+                        *c *= m;
+                    }
+                }
+                blend(self.image.get_pixel_mut(x, y), color);
+            }
+        }
+    }
+}
+
+/// Samples the texture with the nearest filter, returns the color in the `0..1` range.
+fn sample(texture: &RgbaImage, uv: Aabb2<f32>, t: vec2<f32>) -> [f32; 4] {
+    let uv = uv.bottom_left() + uv.size() * t;
+    let (width, height) = texture.dimensions();
+    let x = ((uv.x * width as f32) as u32).min(width - 1);
+    // The images are stored top to bottom
+    let y = (((1.0 - uv.y) * height as f32) as u32).min(height - 1);
+    texture.get_pixel(x, y).0.map(|c| c as f32 / 255.0)
+}
+
+/// Blends the color over the pixel.
+fn blend(pixel: &mut image::Rgba<u8>, color: [f32; 4]) {
+    let src_alpha = color[3];
+    if src_alpha <= 0.0 {
+        return;
+    }
+    let dst = pixel.0.map(|c| c as f32 / 255.0);
+    let alpha = src_alpha + dst[3] * (1.0 - src_alpha);
+    let mut result = [0.0, 0.0, 0.0, alpha];
+    for ((result, src), dst_color) in result.iter_mut().zip(color).zip(dst).take(3) {
+        *result = (src * src_alpha + dst_color * dst[3] * (1.0 - src_alpha)) / alpha;
+    }
+    pixel.0 = result.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8);
+}
@@ -0,0 +1,124 @@
+use super::*;
+
+use image::RgbaImage;
+
+/// Number of the individual problems logged before summarizing the rest.
+const MAX_REPORTED: usize = 20;
+/// Number of neighbourhoods in a row of the coverage sheet, makes a square of the 3^8 patterns.
+const SHEET_COLUMNS: u32 = 81;
+/// Gap between the cells of the coverage sheet in pixels.
+const SHEET_PADDING: u32 = 2;
+
+/// Checks the tileset rules against every possible neighbourhood and logs the problems.
+/// Returns whether every neighbourhood has a matching rule.
+pub fn report(name: &str, config: &TileSetConfig) -> bool {
+    let coverage = config.check_coverage();
+    for connections in coverage.unmatched.iter().take(MAX_REPORTED) {
+        warn!("{name}: no rule matches {}", format_pattern(connections));
+    }
+    if coverage.unmatched.len() > MAX_REPORTED {
+        warn!(
+            "{name}: ...and {} more unmatched neighbourhoods",
+            coverage.unmatched.len() - MAX_REPORTED
+        );
+    }
+    for &rule in &coverage.shadowed {
+        let TileRule { pattern, uv, .. } = &config.tiles[rule];
+        let tile = (uv[0] * config.size.map(|x| x as f32)).map(|x| x.round() as usize);
+        warn!(
+            "{name}: rule {rule} (tile {}, {}) is shadowed by earlier rules: {pattern:?}",
+            tile.x, tile.y
+        );
+    }
+    info!(
+        "{name}: {} of {} neighbourhoods unmatched, {} of {} rules shadowed",
+        coverage.unmatched.len(),
+        Connection::all_patterns().count(),
+        coverage.shadowed.len(),
+        config.tiles.len()
+    );
+    coverage.unmatched.is_empty()
+}
+
+/// Formats the neighbourhood as three rows from the top:
+/// `.` for air, `S` for the same tile, `O` for another tile and `#` for the center.
+fn format_pattern(connections: &[Connection; 8]) -> String {
+    let c = connections.map(|con| match con {
+        Connection::None => '.',
+        Connection::Same => 'S',
+        Connection::Other => 'O',
+    });
+    format!(
+        "{}{}{}/{}#{}/{}{}{}",
+        c[6], c[5], c[4], c[7], c[3], c[0], c[1], c[2]
+    )
+}
+
+/// Renders every neighbourhood with the tile the rules choose for it.
+/// Same neighbours are blue and other tiles are red like in the config images,
+/// the centers without a matching rule are filled with magenta.
+pub fn render_sheet(texture: &RgbaImage, config: &TileSetConfig) -> RgbaImage {
+    let tile_size = vec2(
+        texture.width() / config.size.x as u32,
+        texture.height() / config.size.y as u32,
+    );
+    let cell = tile_size * 3 + vec2(SHEET_PADDING, SHEET_PADDING);
+    let patterns: Vec<_> = Connection::all_patterns().collect();
+    let rows = patterns.len() as u32 / SHEET_COLUMNS;
+    let mut sheet = RgbaImage::new(SHEET_COLUMNS * cell.x, rows * cell.y);
+
+    // Positions of the neighbours in the cell, the image rows go from the top
+    let offsets = [
+        (0, 2),
+        (1, 2),
+        (2, 2),
+        (2, 1),
+        (2, 0),
+        (1, 0),
+        (0, 0),
+        (0, 1),
+    ]
+    .map(|(x, y)| vec2(x, y) * tile_size);
+
+    for (i, connections) in patterns.into_iter().enumerate() {
+        let i = i as u32;
+        let origin = vec2(i % SHEET_COLUMNS, i / SHEET_COLUMNS) * cell;
+        for (con, offset) in connections.iter().zip(offsets) {
+            let color = match con {
+                Connection::None => continue,
+                Connection::Same => [0, 0, 255, 160],
+                Connection::Other => [255, 0, 0, 160],
+            };
+            fill(&mut sheet, origin + offset, tile_size, color);
+        }
+
+        let center = origin + tile_size;
+        match config.find_tile(connections) {
+            Some(rule) => {
+                let uv = config.tiles[rule].uv;
+                // The uv origin is at the bottom left
+                let source = vec2(
+                    uv[3].x * texture.width() as f32,
+                    (1.0 - uv[3].y) * texture.height() as f32,
+                )
+                .map(|x| x.round() as u32);
+                for y in 0..tile_size.y {
+                    for x in 0..tile_size.x {
+                        let pixel = *texture.get_pixel(source.x + x, source.y + y);
+                        sheet.put_pixel(center.x + x, center.y + y, pixel);
+                    }
+                }
+            }
+            None => fill(&mut sheet, center, tile_size, [255, 0, 255, 255]),
+        }
+    }
+    sheet
+}
+
+fn fill(image: &mut RgbaImage, pos: vec2<u32>, size: vec2<u32>, color: [u8; 4]) {
+    for y in pos.y..pos.y + size.y {
+        for x in pos.x..pos.x + size.x {
+            image.put_pixel(x, y, image::Rgba(color));
+        }
+    }
+}
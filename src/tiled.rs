@@ -0,0 +1,771 @@
+use super::*;
+
+/// Size of a single tile in pixels in the exported maps.
+const TILE_PIXELS: usize = PIXELS_PER_UNIT as usize;
+
+/// A map in the Tiled JSON format.
+/// Only orthogonal maps with uncompressed tile layers are supported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledMap {
+    #[serde(rename = "type", default = "TiledMap::default_type")]
+    pub map_type: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default = "TiledMap::default_orientation")]
+    pub orientation: String,
+    #[serde(default = "TiledMap::default_renderorder")]
+    pub renderorder: String,
+    #[serde(default)]
+    pub infinite: bool,
+    pub width: usize,
+    pub height: usize,
+    pub tilewidth: usize,
+    pub tileheight: usize,
+    #[serde(default)]
+    pub nextlayerid: usize,
+    #[serde(default)]
+    pub nextobjectid: usize,
+    pub layers: Vec<TiledLayer>,
+    #[serde(default)]
+    pub tilesets: Vec<TiledTileSet>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<TiledProperty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TiledLayer {
+    #[serde(rename = "tilelayer")]
+    TileLayer(TiledTileLayer),
+    #[serde(rename = "objectgroup")]
+    ObjectGroup(TiledObjectGroup),
+    /// Image and group layers are ignored.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledTileLayer {
+    pub id: usize,
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    /// Tile gids in rows from top to bottom. Gid 0 means no tile.
+    pub data: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledObjectGroup {
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    #[serde(default = "TiledObjectGroup::default_draworder")]
+    pub draworder: String,
+    pub objects: Vec<TiledObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledObject {
+    pub id: usize,
+    #[serde(default)]
+    pub name: String,
+    /// Called `class` since Tiled 1.9.
+    #[serde(rename = "type", alias = "class", default)]
+    pub object_type: String,
+    /// Position of the top-left corner in pixels (y points down).
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub width: f32,
+    #[serde(default)]
+    pub height: f32,
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub point: bool,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<TiledProperty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledTileSet {
+    pub firstgid: u32,
+    /// Path to an external tileset. Those are not loaded,
+    /// so their tiles are mapped by the order in [Tile::all].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tilecount: u32,
+    #[serde(default)]
+    pub columns: u32,
+    #[serde(default)]
+    pub tilewidth: usize,
+    #[serde(default)]
+    pub tileheight: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiles: Vec<TiledTile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledTile {
+    pub id: u32,
+    /// Name of the [Tile] this tile represents.
+    #[serde(rename = "type", alias = "class", default)]
+    pub tile_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledProperty {
+    pub name: String,
+    #[serde(rename = "type", default = "TiledProperty::default_type")]
+    pub property_type: String,
+    pub value: serde_json::Value,
+}
+
+/// Tiled stores flip flags in the highest bits of the gid.
+const GID_MASK: u32 = 0x1FFF_FFFF;
+
+impl TiledMap {
+    fn default_type() -> String {
+        "map".to_string()
+    }
+
+    fn default_orientation() -> String {
+        "orthogonal".to_string()
+    }
+
+    fn default_renderorder() -> String {
+        "right-down".to_string()
+    }
+
+    /// Converts the level into a Tiled map saved into `map_dir`.
+    /// `tiles_dir` is the directory with the tileset images referenced by the map,
+    /// the images are referenced relative to `map_dir`.
+    pub fn from_level(
+        level: &Level,
+        tiles_dir: &std::path::Path,
+        map_dir: &std::path::Path,
+    ) -> Self {
+        let tiles_dir = relative_path(map_dir, tiles_dir);
+        let tiles: Vec<Tile> = Tile::all()
+            .into_iter()
+            .filter(|tile| !matches!(tile, Tile::Air))
+            .collect();
+        let tileset = TiledTileSet {
+            firstgid: 1,
+            source: None,
+            name: "tiles".to_string(),
+            tilecount: tiles.len() as u32,
+            columns: 0,
+            tilewidth: TILE_PIXELS,
+            tileheight: TILE_PIXELS,
+            tiles: tiles
+                .iter()
+                .enumerate()
+                .map(|(id, tile)| TiledTile {
+                    id: id as u32,
+                    tile_type: format!("{tile:?}"),
+                    image: Some(
+                        tiles_dir
+                            .join(format!("{}.png", tile_image_name(tile)))
+                            .to_string_lossy()
+                            .to_string(),
+                    ),
+                })
+                .collect(),
+        };
+
+        let size = level.size;
+        let data = (0..size.y)
+            .rev()
+            .flat_map(|y| (0..size.x).map(move |x| vec2(x, y)))
+            .map(|pos| {
+                level
+                    .tiles
+                    .get_tile_isize(pos.map(|x| x as isize))
+                    .and_then(|tile| tiles.iter().position(|&t| t == tile))
+                    .map_or(0, |i| i as u32 + tileset.firstgid)
+            })
+            .collect();
+
+        let transform = TiledTransform::new(level, TILE_PIXELS, TILE_PIXELS);
+        let mut objects = Vec::new();
+        let mut push = |object_type: &str, aabb: Option<Aabb2<Coord>>, pos, properties| {
+            let (x, y, width, height) = match aabb {
+                Some(aabb) => transform.rect_to_tiled(aabb),
+                None => {
+                    let (x, y) = transform.pos_to_tiled(pos);
+                    (x, y, 0.0, 0.0)
+                }
+            };
+            objects.push(TiledObject {
+                id: objects.len() + 1,
+                name: String::new(),
+                object_type: object_type.to_string(),
+                x,
+                y,
+                width,
+                height,
+                rotation: 0.0,
+                point: aabb.is_none(),
+                visible: true,
+                properties,
+            });
+        };
+
+        push("spawn", None, level.spawn_point, vec![]);
+        push("finish", Some(level.finish().raw()), vec2::ZERO, vec![]);
+        for coin in &level.coins {
+            push("coin", Some(coin.collider.raw()), vec2::ZERO, vec![]);
+        }
+        for hazard in &level.hazards {
+            let properties = vec![TiledProperty::string(
+                "hazard_type",
+                format!("{:?}", hazard.hazard_type),
+            )];
+            push("hazard", Some(hazard.sprite), vec2::ZERO, properties);
+        }
+        for prop in &level.props {
+            let properties = vec![TiledProperty::string(
+                "prop_type",
+                format!("{:?}", prop.prop_type),
+            )];
+            push("prop", Some(prop.sprite), vec2::ZERO, properties);
+        }
+        for light in &level.spotlights {
+            let properties = vec![
+                TiledProperty::float("angle", light.angle),
+                TiledProperty::float("angle_range", light.angle_range),
+                TiledProperty::color("color", light.color),
+                TiledProperty::float("intensity", light.intensity),
+                TiledProperty::float("max_distance", light.max_distance.as_f32()),
+                TiledProperty::float("volume", light.volume),
+                TiledProperty::float("source_radius", light.source_radius.as_f32()),
+                TiledProperty::string("falloff", format!("{:?}", light.falloff)),
+                TiledProperty::float("angle_softness", light.angle_softness),
+            ];
+            let properties = [properties, animation_to_tiled(&light.animation)].concat();
+            push("spotlight", None, light.position, properties);
+        }
+        for light in &level.point_lights {
+            let properties = vec![
+                TiledProperty::color("color", light.color),
+                TiledProperty::float("intensity", light.intensity),
+                TiledProperty::float("max_distance", light.max_distance.as_f32()),
+                TiledProperty::float("volume", light.volume),
+                TiledProperty::float("source_radius", light.source_radius.as_f32()),
+                TiledProperty::string("falloff", format!("{:?}", light.falloff)),
+            ];
+            let properties = [properties, animation_to_tiled(&light.animation)].concat();
+            push("point_light", None, light.position, properties);
+        }
+        for zone in &level.camera_zones {
+            let mut properties = vec![TiledProperty::string("mode", format!("{:?}", zone.mode))];
+            if let Some(fov) = zone.fov {
+                properties.push(TiledProperty::float("fov", fov));
+            }
+            push("camera", Some(zone.area), vec2::ZERO, properties);
+        }
+
+        let mut properties = vec![
+            TiledProperty::bool("drill_allowed", level.drill_allowed),
+            TiledProperty::color("global_light_color", level.global_light.color),
+            TiledProperty::float("global_light_intensity", level.global_light.intensity),
+        ];
+        if let Some(next_level) = &level.next_level {
+            properties.push(TiledProperty::string("next_level", next_level.clone()));
+        }
+        if let Some(grade) = &level.grade {
+            properties.push(TiledProperty::string("grade", grade.clone()));
+        }
+
+        Self {
+            map_type: Self::default_type(),
+            version: "1.8".to_string(),
+            orientation: Self::default_orientation(),
+            renderorder: Self::default_renderorder(),
+            infinite: false,
+            width: size.x,
+            height: size.y,
+            tilewidth: TILE_PIXELS,
+            tileheight: TILE_PIXELS,
+            nextlayerid: 3,
+            nextobjectid: objects.len() + 1,
+            layers: vec![
+                TiledLayer::TileLayer(TiledTileLayer {
+                    id: 1,
+                    name: "tiles".to_string(),
+                    width: size.x,
+                    height: size.y,
+                    x: 0,
+                    y: 0,
+                    opacity: 1.0,
+                    visible: true,
+                    data,
+                }),
+                TiledLayer::ObjectGroup(TiledObjectGroup {
+                    id: 2,
+                    name: "objects".to_string(),
+                    x: 0,
+                    y: 0,
+                    opacity: 1.0,
+                    visible: true,
+                    draworder: TiledObjectGroup::default_draworder(),
+                    objects,
+                }),
+            ],
+            tilesets: vec![tileset],
+            properties,
+        }
+    }
+
+    /// Converts the Tiled map into a level.
+    /// All tile layers are merged, later layers overwriting earlier ones.
+    pub fn to_level(&self) -> anyhow::Result<Level> {
+        if self.orientation != "orthogonal" {
+            anyhow::bail!("only orthogonal maps are supported");
+        }
+        if self.infinite {
+            anyhow::bail!("infinite maps are not supported");
+        }
+
+        let size = vec2(self.width, self.height);
+        let mut level = Level::new(size);
+        let gids = self.gid_tiles();
+
+        for layer in &self.layers {
+            let TiledLayer::TileLayer(layer) = layer else {
+                continue;
+            };
+            if layer.data.len() != layer.width * layer.height {
+                anyhow::bail!(
+                    "tile layer {:?} has {} tiles, expected {}x{}",
+                    layer.name,
+                    layer.data.len(),
+                    layer.width,
+                    layer.height
+                );
+            }
+            for (i, &gid) in layer.data.iter().enumerate() {
+                let gid = gid & GID_MASK;
+                if gid == 0 {
+                    continue;
+                }
+                let Some(&tile) = gids.get(&gid) else {
+                    warn!("Unknown tile gid {gid} in layer {:?}", layer.name);
+                    continue;
+                };
+                let x = (i % layer.width) as isize + (layer.x as isize);
+                let y = (i / layer.width) as isize + (layer.y as isize);
+                let pos = vec2(x, size.y as isize - 1 - y);
+                level.tiles.set_tile_isize(pos, tile);
+            }
+        }
+
+        let transform = TiledTransform::new(&level, self.tilewidth, self.tileheight);
+        if let Some(value) = find_property(&self.properties, "drill_allowed") {
+            level.drill_allowed = value.as_bool().unwrap_or(true);
+        }
+        if let Some(color) = find_property(&self.properties, "global_light_color")
+            .and_then(|value| value.as_str())
+            .and_then(color_from_tiled)
+        {
+            level.global_light.color = color;
+        }
+        if let Some(value) = find_property(&self.properties, "global_light_intensity")
+            .and_then(|value| value.as_f64())
+        {
+            level.global_light.intensity = value as f32;
+        }
+        level.next_level = find_property(&self.properties, "next_level")
+            .and_then(|value| value.as_str())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string());
+        level.grade = find_property(&self.properties, "grade")
+            .and_then(|value| value.as_str())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string());
+
+        for layer in &self.layers {
+            let TiledLayer::ObjectGroup(layer) = layer else {
+                continue;
+            };
+            for object in &layer.objects {
+                let aabb = transform.rect_from_tiled(object);
+                let pos = transform.pos_from_tiled(object.x, object.y);
+                let float = |name: &str| {
+                    find_property(&object.properties, name)
+                        .and_then(|value| value.as_f64())
+                        .map(|value| value as f32)
+                };
+                match object.object_type.as_str() {
+                    "spawn" => level.spawn_point = pos,
+                    "finish" => {
+                        level.finish = if object.point {
+                            pos
+                        } else {
+                            aabb.bottom_left()
+                        }
+                    }
+                    "coin" => level.coins.push(Coin {
+                        collider: Collider::new(aabb),
+                        collected: false,
+                    }),
+                    "hazard" => {
+                        let name = find_property(&object.properties, "hazard_type")
+                            .and_then(|value| value.as_str())
+                            .unwrap_or(&object.name);
+                        let Some(hazard) = HazardType::all()
+                            .into_iter()
+                            .find(|hazard| format!("{hazard:?}") == name)
+                        else {
+                            warn!("Unknown hazard type {name:?} of object {}", object.id);
+                            continue;
+                        };
+                        // Recalculate the direction from the surrounding tiles
+                        let grid_pos = level.grid.world_to_grid(aabb.center()).0;
+                        level.place_hazard(grid_pos, hazard);
+                    }
+                    "prop" => {
+                        let name = find_property(&object.properties, "prop_type")
+                            .and_then(|value| value.as_str())
+                            .unwrap_or(&object.name);
+                        let Some(prop) = PropType::all()
+                            .into_iter()
+                            .find(|prop| format!("{prop:?}") == name)
+                        else {
+                            warn!("Unknown prop type {name:?} of object {}", object.id);
+                            continue;
+                        };
+                        level.props.push(Prop {
+                            sprite: aabb,
+                            prop_type: prop,
+                        });
+                    }
+                    "spotlight" => {
+                        let default = SpotlightSource::default();
+                        level.spotlights.push(SpotlightSource {
+                            position: if object.point { pos } else { aabb.center() },
+                            angle: float("angle").unwrap_or(default.angle),
+                            angle_range: float("angle_range").unwrap_or(default.angle_range),
+                            color: find_property(&object.properties, "color")
+                                .and_then(|value| value.as_str())
+                                .and_then(color_from_tiled)
+                                .unwrap_or(default.color),
+                            intensity: float("intensity").unwrap_or(default.intensity),
+                            max_distance: float("max_distance")
+                                .map_or(default.max_distance, Coord::new),
+                            volume: float("volume").unwrap_or(default.volume),
+                            source_radius: float("source_radius")
+                                .map_or(default.source_radius, Coord::new),
+                            falloff: find_property(&object.properties, "falloff")
+                                .and_then(|value| value.as_str())
+                                .and_then(|name| {
+                                    LightFalloff::all()
+                                        .into_iter()
+                                        .find(|falloff| format!("{falloff:?}") == name)
+                                })
+                                .unwrap_or(default.falloff),
+                            angle_softness: float("angle_softness")
+                                .unwrap_or(default.angle_softness),
+                            animation: animation_from_tiled(&object.properties),
+                        });
+                    }
+                    "point_light" => {
+                        let default = PointLightSource::default();
+                        level.point_lights.push(PointLightSource {
+                            position: if object.point { pos } else { aabb.center() },
+                            color: find_property(&object.properties, "color")
+                                .and_then(|value| value.as_str())
+                                .and_then(color_from_tiled)
+                                .unwrap_or(default.color),
+                            intensity: float("intensity").unwrap_or(default.intensity),
+                            max_distance: float("max_distance")
+                                .map_or(default.max_distance, Coord::new),
+                            volume: float("volume").unwrap_or(default.volume),
+                            source_radius: float("source_radius")
+                                .map_or(default.source_radius, Coord::new),
+                            falloff: find_property(&object.properties, "falloff")
+                                .and_then(|value| value.as_str())
+                                .and_then(|name| {
+                                    LightFalloff::all()
+                                        .into_iter()
+                                        .find(|falloff| format!("{falloff:?}") == name)
+                                })
+                                .unwrap_or(default.falloff),
+                            animation: animation_from_tiled(&object.properties),
+                        });
+                    }
+                    "camera" => {
+                        let name = find_property(&object.properties, "mode")
+                            .and_then(|value| value.as_str())
+                            .unwrap_or(&object.name);
+                        let Some(mode) = CameraZoneMode::all()
+                            .into_iter()
+                            .find(|mode| format!("{mode:?}") == name)
+                        else {
+                            warn!("Unknown camera mode {name:?} of object {}", object.id);
+                            continue;
+                        };
+                        level.camera_zones.push(CameraZone {
+                            area: aabb,
+                            mode,
+                            fov: float("fov"),
+                        });
+                    }
+                    ty => {
+                        warn!("Ignoring object {} of unknown type {ty:?}", object.id);
+                    }
+                }
+            }
+        }
+
+        Ok(level)
+    }
+
+    /// Maps global tile ids to tiles.
+    fn gid_tiles(&self) -> HashMap<u32, Tile> {
+        let mut gids = HashMap::new();
+        for tileset in &self.tilesets {
+            let named: Vec<_> = tileset
+                .tiles
+                .iter()
+                .filter_map(|tile| {
+                    Tile::all()
+                        .into_iter()
+                        .find(|t| format!("{t:?}") == tile.tile_type)
+                        .map(|t| (tile.id, t))
+                })
+                .collect();
+            if named.is_empty() {
+                // Fallback to the order of the tiles
+                let tiles = Tile::all()
+                    .into_iter()
+                    .filter(|tile| !matches!(tile, Tile::Air));
+                for (id, tile) in tiles.enumerate() {
+                    gids.insert(tileset.firstgid + id as u32, tile);
+                }
+            } else {
+                for (id, tile) in named {
+                    gids.insert(tileset.firstgid + id, tile);
+                }
+            }
+        }
+        gids
+    }
+}
+
+impl TiledObjectGroup {
+    fn default_draworder() -> String {
+        "topdown".to_string()
+    }
+}
+
+impl TiledProperty {
+    fn default_type() -> String {
+        "string".to_string()
+    }
+
+    fn new(name: &str, property_type: &str, value: serde_json::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            property_type: property_type.to_string(),
+            value,
+        }
+    }
+
+    fn string(name: &str, value: String) -> Self {
+        Self::new(name, "string", value.into())
+    }
+
+    fn float(name: &str, value: f32) -> Self {
+        Self::new(name, "float", value.into())
+    }
+
+    fn bool(name: &str, value: bool) -> Self {
+        Self::new(name, "bool", value.into())
+    }
+
+    fn color(name: &str, value: Rgba<f32>) -> Self {
+        Self::new(name, "color", color_to_tiled(value).into())
+    }
+}
+
+/// Conversion between world coordinates and Tiled pixel coordinates.
+struct TiledTransform {
+    bounds: Aabb2<Coord>,
+    /// World units per pixel.
+    scale: vec2<Coord>,
+}
+
+impl TiledTransform {
+    fn new(level: &Level, tilewidth: usize, tileheight: usize) -> Self {
+        let pixels = vec2(tilewidth, tileheight).map(|x| Coord::new(x.max(1) as f32));
+        Self {
+            bounds: level.bounds(),
+            scale: level.grid.cell_size / pixels,
+        }
+    }
+
+    fn pos_to_tiled(&self, pos: vec2<Coord>) -> (f32, f32) {
+        let x = (pos.x - self.bounds.min.x) / self.scale.x;
+        let y = (self.bounds.max.y - pos.y) / self.scale.y;
+        (x.as_f32(), y.as_f32())
+    }
+
+    fn pos_from_tiled(&self, x: f32, y: f32) -> vec2<Coord> {
+        vec2(
+            self.bounds.min.x + Coord::new(x) * self.scale.x,
+            self.bounds.max.y - Coord::new(y) * self.scale.y,
+        )
+    }
+
+    fn rect_to_tiled(&self, aabb: Aabb2<Coord>) -> (f32, f32, f32, f32) {
+        let (x, y) = self.pos_to_tiled(vec2(aabb.min.x, aabb.max.y));
+        let size = aabb.size() / self.scale;
+        (x, y, size.x.as_f32(), size.y.as_f32())
+    }
+
+    fn rect_from_tiled(&self, object: &TiledObject) -> Aabb2<Coord> {
+        Aabb2::from_corners(
+            self.pos_from_tiled(object.x, object.y),
+            self.pos_from_tiled(object.x + object.width, object.y + object.height),
+        )
+    }
+}
+
+/// Returns the path to `path` from the `base` directory.
+fn relative_path(base: &std::path::Path, path: &std::path::Path) -> std::path::PathBuf {
+    use std::path::Component;
+    let canonical =
+        |path: &std::path::Path| path.canonicalize().unwrap_or_else(|_| path.to_owned());
+    let (base, path) = (canonical(base), canonical(path));
+    let base: Vec<Component> = base.components().collect();
+    let path: Vec<Component> = path.components().collect();
+    let common = base.iter().zip(&path).take_while(|(a, b)| a == b).count();
+    std::iter::repeat(Component::ParentDir)
+        .take(base.len() - common)
+        .chain(path[common..].iter().copied())
+        .collect()
+}
+
+fn tile_image_name(tile: &Tile) -> &'static str {
+    match tile {
+        Tile::Air => "air",
+        Tile::Grass => "grass",
+        Tile::Stone => "stone",
+    }
+}
+
+/// Light animation as Tiled properties, omitting the disabled effects.
+fn animation_to_tiled(animation: &LightAnimation) -> Vec<TiledProperty> {
+    let mut properties = Vec::new();
+    if animation.flicker > 0.0 {
+        properties.push(TiledProperty::float("flicker", animation.flicker));
+        properties.push(TiledProperty::float(
+            "flicker_speed",
+            animation.flicker_speed,
+        ));
+    }
+    if animation.pulse > 0.0 {
+        properties.push(TiledProperty::float("pulse", animation.pulse));
+        properties.push(TiledProperty::float("pulse_period", animation.pulse_period));
+    }
+    if let Some(color) = animation.color_cycle {
+        properties.push(TiledProperty::color("color_cycle", color));
+        properties.push(TiledProperty::float("color_period", animation.color_period));
+    }
+    if let Some(path) = animation.path {
+        properties.push(TiledProperty::float("path_x", path.x.as_f32()));
+        properties.push(TiledProperty::float("path_y", path.y.as_f32()));
+        properties.push(TiledProperty::float("path_period", animation.path_period));
+    }
+    properties
+}
+
+fn animation_from_tiled(properties: &[TiledProperty]) -> LightAnimation {
+    let default = LightAnimation::default();
+    let float = |name: &str| {
+        find_property(properties, name)
+            .and_then(|value| value.as_f64())
+            .map(|value| value as f32)
+    };
+    let path = match (float("path_x"), float("path_y")) {
+        (None, None) => None,
+        (x, y) => Some(vec2(x.unwrap_or(0.0), y.unwrap_or(0.0)).map(Coord::new)),
+    };
+    LightAnimation {
+        flicker: float("flicker").unwrap_or(default.flicker),
+        flicker_speed: float("flicker_speed").unwrap_or(default.flicker_speed),
+        pulse: float("pulse").unwrap_or(default.pulse),
+        pulse_period: float("pulse_period").unwrap_or(default.pulse_period),
+        color_cycle: find_property(properties, "color_cycle")
+            .and_then(|value| value.as_str())
+            .and_then(color_from_tiled),
+        color_period: float("color_period").unwrap_or(default.color_period),
+        path,
+        path_period: float("path_period").unwrap_or(default.path_period),
+    }
+}
+
+fn find_property<'a>(properties: &'a [TiledProperty], name: &str) -> Option<&'a serde_json::Value> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| &property.value)
+}
+
+/// Converts the color to the `#AARRGGBB` format used by Tiled.
+fn color_to_tiled(color: Rgba<f32>) -> String {
+    let [r, g, b, a] =
+        [color.r, color.g, color.b, color.a].map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{a:02x}{r:02x}{g:02x}{b:02x}")
+}
+
+/// Parses a color in the `#AARRGGBB` or `#RRGGBB` format used by Tiled.
+fn color_from_tiled(color: &str) -> Option<Rgba<f32>> {
+    let hex = color.strip_prefix('#')?;
+    let (alpha, rgb) = match hex.len() {
+        8 => hex.split_at(2),
+        6 => ("ff", hex),
+        _ => return None,
+    };
+    let alpha = u8::from_str_radix(alpha, 16).ok()?;
+    let rgb = u32::from_str_radix(rgb, 16).ok()?;
+    let channel = |shift: u32| ((rgb >> shift) & 0xFF) as f32 / 255.0;
+    Some(Rgba::new(
+        channel(16),
+        channel(8),
+        channel(0),
+        alpha as f32 / 255.0,
+    ))
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
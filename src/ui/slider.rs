@@ -30,6 +30,11 @@ impl<'a> Slider<'a> {
     pub fn get_change(&self) -> Option<f64> {
         self.change.borrow_mut().take()
     }
+
+    /// Whether the slider is being dragged.
+    pub fn is_captured(&self) -> bool {
+        self.sense.is_captured()
+    }
 }
 
 impl<'a> Widget for Slider<'a> {
@@ -0,0 +1,102 @@
+//! Browser-specific helpers for storing and transferring files.
+
+use super::*;
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+fn js_err(err: JsValue) -> anyhow::Error {
+    anyhow::anyhow!("{err:?}")
+}
+
+fn document() -> anyhow::Result<web_sys::Document> {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| anyhow::anyhow!("document is not available"))
+}
+
+fn storage() -> anyhow::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| anyhow::anyhow!("browser storage is not available"))
+}
+
+/// Reads the value from the browser's local storage.
+pub fn load_string(key: &str) -> Option<String> {
+    storage().ok()?.get_item(key).ok().flatten()
+}
+
+/// Writes the value into the browser's local storage.
+pub fn save_string(key: &str, value: &str) -> anyhow::Result<()> {
+    storage()?.set_item(key, value).map_err(js_err)
+}
+
+/// Makes the browser download a file with the given contents.
+pub fn download(file_name: &str, contents: &str) -> anyhow::Result<()> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("application/json");
+    let blob =
+        web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).map_err(js_err)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_err)?;
+
+    let anchor: web_sys::HtmlAnchorElement = document()?
+        .create_element("a")
+        .map_err(js_err)?
+        .dyn_into()
+        .map_err(|_| anyhow::anyhow!("failed to create a download link"))?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    // The download starts asynchronously, so the url has to outlive the click
+    let revoke = Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    web_sys::window()
+        .ok_or_else(|| anyhow::anyhow!("window is not available"))?
+        .set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), 1000)
+        .map_err(js_err)?;
+    Ok(())
+}
+
+/// Asks the user to select a json file.
+/// The receiver gets the name and the contents of the file once it is read.
+pub fn upload() -> anyhow::Result<futures::channel::oneshot::Receiver<(String, String)>> {
+    let input: web_sys::HtmlInputElement = document()?
+        .create_element("input")
+        .map_err(js_err)?
+        .dyn_into()
+        .map_err(|_| anyhow::anyhow!("failed to create a file input"))?;
+    input.set_type("file");
+    input.set_accept(".json,application/json");
+
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    let mut sender = Some(sender);
+    let onchange = {
+        let input = input.clone();
+        Closure::<dyn FnMut()>::new(move || {
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let Some(sender) = sender.take() else {
+                return;
+            };
+            let name = file.name();
+            let text = wasm_bindgen_futures::JsFuture::from(file.text());
+            wasm_bindgen_futures::spawn_local(async move {
+                match text.await.map(|text| text.as_string()) {
+                    Ok(Some(text)) => {
+                        let _ = sender.send((name, text));
+                    }
+                    _ => error!("Failed to read the file {name}"),
+                }
+            });
+        })
+    };
+    input.set_onchange(Some(onchange.as_ref().unchecked_ref()));
+    // The input lives until the page is closed, so the callback has to as well
+    onchange.forget();
+    input.click();
+
+    Ok(receiver)
+}